@@ -4,4 +4,130 @@ use anchor_lang::prelude::*;
 pub enum IdeaPadErrorCode {
     #[msg("Numerical Overflow!")]
     NumericalOverflow,
+    #[msg("Draft projects can only move to Raising or Cancelled")]
+    InvalidTransitionFromDraft,
+    #[msg("Raising projects can only move to Funded, Failed or Cancelled")]
+    InvalidTransitionFromRaising,
+    #[msg("Funded projects can only move to Complete")]
+    InvalidTransitionFromFunded,
+    #[msg("Project is Complete, Failed or Cancelled and can no longer change state")]
+    ProjectFinalized,
+    #[msg("Project needs at least one contribution reward before raising")]
+    NoContributionRewards,
+    #[msg("Redeemption stamp has not been reached")]
+    RedeemptionStampNotReached,
+    #[msg("Project is not Complete and the redeemption stamp has not passed")]
+    ClaimNotAvailable,
+    #[msg("Stake has already been claimed")]
+    AlreadyClaimed,
+    #[msg("Contribution reward is sold out")]
+    RewardSoldOut,
+    #[msg("Quantity cannot be set below the amount already sold")]
+    QuantityBelowSold,
+    #[msg("Contribution rewards can only be changed while the project is Draft or Raising")]
+    RewardLocked,
+    #[msg("Validator is not in the validator registry")]
+    ValidatorNotRegistered,
+    #[msg("Validator is already in the validator registry")]
+    ValidatorAlreadyRegistered,
+    #[msg("Validator registry is full")]
+    ValidatorRegistryFull,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Fee numerator cannot be greater than the denominator")]
+    FeeTooHigh,
+    #[msg("Signer is not the pending protocol admin")]
+    InvalidPendingAdmin,
+    #[msg("Fee is above the protocol maximum")]
+    FeeAboveProtocolMax,
+    #[msg("Protocol fee share must be a percentage")]
+    InvalidProtocolFeeShare,
+    #[msg("No fee lst has accrued since the last harvest")]
+    NothingToHarvest,
+    #[msg("Creator lst account is required when harvesting as lst")]
+    MissingCreatorLstAccount,
+    #[msg("Validator is not part of the project stake pool")]
+    ValidatorNotInPool,
+    #[msg("Validator has a transient stake account in flight")]
+    RebalanceInProgress,
+    #[msg("Reserve is already within the buffer")]
+    NothingToRebalance,
+    #[msg("Validator is already part of the project stake pool")]
+    ValidatorAlreadyInPool,
+    #[msg("Project has reached its maximum number of validators")]
+    ProjectValidatorsFull,
+    #[msg("A project needs at least one validator")]
+    LastProjectValidator,
+    #[msg("Validator is already at its target allocation")]
+    ValidatorAtTargetAllocation,
+    #[msg("Raise end must be in the future")]
+    RaiseEndInPast,
+    #[msg("Raise has ended")]
+    RaiseEnded,
+    #[msg("Raise has not ended yet")]
+    RaiseNotEnded,
+    #[msg("Funded and Failed are set by resolve_raise")]
+    RaiseNotResolvable,
+    #[msg("Refunds are only available for failed or cancelled projects")]
+    RefundNotAvailable,
+    #[msg("Staker lst account is required when refunding as lst")]
+    MissingStakerLstAccount,
+    #[msg("Receipt nft does not belong to this contribution reward")]
+    ReceiptNotFromProject,
+    #[msg("Project is not accepting contributions")]
+    ProjectNotRaising,
+    #[msg("Deposit is below the project minimum stake amount")]
+    BelowMinStakeAmount,
+    #[msg("Deposit is below the contribution reward cost")]
+    BelowRewardCost,
+    #[msg("Contribution reward belongs to another project")]
+    RewardProjectMismatch,
+    #[msg("Entitlement accounts must be the project rewards in order followed by receipt pairs")]
+    InvalidEntitlementAccounts,
+    #[msg("Receipt is not held by the backer")]
+    ReceiptNotHeld,
+    #[msg("Rewards can only be redeemed once the project is funded")]
+    RewardNotRedeemable,
+    #[msg("Reward has already been fulfilled")]
+    RewardAlreadyFulfilled,
+    #[msg("Reward name, symbol or uri is too long")]
+    RewardMetadataTooLong,
+    #[msg("Project configuration can only change while in draft")]
+    ProjectConfigLocked,
+    #[msg("Lst is not whitelisted")]
+    LstNotWhitelisted,
+    #[msg("Lst is already whitelisted")]
+    LstAlreadyWhitelisted,
+    #[msg("Lst registry is full")]
+    LstRegistryFull,
+    #[msg("Source stake pool does not back this lst")]
+    InvalidSourceStakePool,
+    #[msg("Source stake pool has not been updated this epoch")]
+    StaleSourcePoolRate,
+    #[msg("Reward accounts must be every project reward in index order")]
+    InvalidRewardAccounts,
+    #[msg("Early withdrawal is only available while the project is raising or funded")]
+    EarlyWithdrawNotAvailable,
+    #[msg("Withdraw amount must be positive and within the vault balance")]
+    InvalidWithdrawAmount,
+    #[msg("No downgraded receipt is pending for this reward")]
+    NoPendingReceipt,
+    #[msg("Remaining deposit covers no reward, withdraw it in full")]
+    WithdrawBelowLowestTier,
+    #[msg("Stake to split and stake to receive are required when claiming as stake")]
+    MissingStakeAccounts,
+    #[msg("Signer is not the holder the downgraded receipt is pending for")]
+    NotPendingReceiptHolder,
+    #[msg("Quantity can only be raised while the project is raising")]
+    QuantityCanOnlyIncrease,
+    #[msg("Protocol config can only be initialized by the program upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Redeemption stamp must not be before the raise end")]
+    RedeemptionBeforeRaiseEnd,
+    #[msg("Deposited stake account is not delegated")]
+    StakeNotDelegated,
+    #[msg("Receipt does not belong to this deposit")]
+    ReceiptPositionMismatch,
+    #[msg("Reward of this receipt has already been redeemed")]
+    ReceiptRedeemed,
 }
@@ -1,13 +1,20 @@
-use std::{mem, str::FromStr};
+// The anchor macros check cfgs of the solana target and anchor features this crate never declares
+#![allow(unexpected_cfgs)]
 
-use anchor_lang::{prelude::*, anchor_lang::solana_program::stake::state::Stake};
+use std::mem;
+
+use anchor_lang::prelude::*;
 
 declare_id!("49KpHHeP9Hx2TBnHYLZvVYTpc1q2bt2NTvZdr4bMfFea");
 
 mod error;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    metadata::{Metadata, MetadataAccount},
+    metadata::{
+        mpl_token_metadata::types::{CollectionDetails, DataV2},
+        CreateMasterEditionV3, CreateMetadataAccountsV3, Metadata, MetadataAccount,
+        SetAndVerifySizedCollectionItem,
+    },
     token::{Mint, Token, TokenAccount},
 };
 
@@ -16,28 +23,60 @@ use error::IdeaPadErrorCode;
 #[program]
 pub mod ideapad_programs {
 
-    use anchor_lang::solana_program::program::{invoke, invoke_signed};
-    use spl_stake_pool::state::Fee;
+    use anchor_lang::solana_program::program::invoke_signed;
+    use anchor_lang::solana_program::stake::{state::StakeStateV2, tools::get_minimum_delegation};
+    use spl_stake_pool::state::ValidatorList;
 
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_project<'info>(
         ctx: Context<'_, '_, 'info, 'info, CreateProject<'info>>,
         seed: Vec<u8>,
         redeemption_stamp: Option<i64>,
         min_stake_amount: u64,
+        goal: u64,
+        raise_end_stamp: i64,
+        epoch_fee: Option<FeeRate>,
+        deposit_fee: Option<FeeRate>,
+        withdrawal_fee: Option<FeeRate>,
     ) -> Result<()> {
+        let protocol_config = &ctx.accounts.protocol_config;
+        let epoch_fee =
+            protocol_config.project_fee(epoch_fee.unwrap_or(protocol_config.epoch_fee))?;
+        let deposit_fee =
+            protocol_config.project_fee(deposit_fee.unwrap_or(protocol_config.deposit_fee))?;
+        let withdrawal_fee = protocol_config
+            .project_fee(withdrawal_fee.unwrap_or(protocol_config.withdrawal_fee))?;
+
+        require!(
+            raise_end_stamp > Clock::get()?.unix_timestamp,
+            IdeaPadErrorCode::RaiseEndInPast
+        );
+        require!(
+            redeemption_stamp.map_or(true, |stamp| stamp >= raise_end_stamp),
+            IdeaPadErrorCode::RedeemptionBeforeRaiseEnd
+        );
+
         ctx.accounts.project.init(
             ctx.accounts.authority.key(),
             redeemption_stamp,
             min_stake_amount,
+            goal,
+            raise_end_stamp,
             ctx.accounts.stake_pool.key(),
             ctx.accounts.pool_mint.key(),
             seed,
             ctx.bumps.project,
         )?;
 
-        let bump = ctx.bumps.project;
+        ctx.accounts
+            .stake_pool_manager
+            .init(ctx.accounts.project.key(), ctx.bumps.stake_pool_manager)?;
+
+        let project_key = ctx.accounts.project.key();
+        let bump = ctx.bumps.stake_pool_manager;
+        let deposit_authority_bump = ctx.bumps.deposit_authority;
 
         let init_pool_ix = spl_stake_pool::instruction::initialize(
             &ctx.accounts.stake_pool_program.key(),
@@ -50,21 +89,14 @@ pub mod ideapad_programs {
             &ctx.accounts.pool_mint.key(),
             &ctx.accounts.project_fee_account.key(),
             &ctx.accounts.token_program.key(),
-            None,
-            Fee {
-                denominator: 1,
-                numerator: 1,
-            },
-            Fee {
-                denominator: 0,
-                numerator: 0,
-            },
-            Fee {
-                denominator: 0,
-                numerator: 0,
-            },
+            // Set as both the stake and the sol deposit authority of the pool
+            Some(ctx.accounts.deposit_authority.key()),
+            epoch_fee.into(),
+            withdrawal_fee.into(),
+            deposit_fee.into(),
+            // The protocol share is taken once, from harvested fees, so no referral fee
             0,
-            1,
+            ValidatorAllocation::MAX_VALIDATORS as u32,
         );
 
         invoke_signed(
@@ -81,18 +113,22 @@ pub mod ideapad_programs {
                 ctx.accounts.pool_mint.to_account_info(),
                 ctx.accounts.project_fee_account.to_account_info(),
                 ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.deposit_authority.to_account_info(),
+            ],
+            &[
+                &[b"pool_manager".as_ref(), project_key.as_ref(), &[bump]],
+                &[
+                    b"deposit_authority".as_ref(),
+                    project_key.as_ref(),
+                    &[deposit_authority_bump],
+                ],
             ],
-            &[&[
-                b"pool_manager".as_ref(),
-                &ctx.accounts.project.key().to_bytes(),
-                &[bump],
-            ]],
         )?;
 
         let add_validator_ix = spl_stake_pool::instruction::add_validator_to_pool(
             &ctx.accounts.stake_pool_program.key(),
             &ctx.accounts.stake_pool.key(),
-            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.stake_pool_manager.key(),
             &ctx.accounts.reserve_stake.key(),
             &ctx.accounts.stake_pool_withdrawal_authority.key(),
             &ctx.accounts.validator_list.key(),
@@ -105,6 +141,7 @@ pub mod ideapad_programs {
             &add_validator_ix,
             &[
                 ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_manager.to_account_info(),
                 ctx.accounts
                     .stake_pool_withdrawal_authority
                     .to_account_info(),
@@ -119,11 +156,14 @@ pub mod ideapad_programs {
                 ctx.accounts.system_program.to_account_info(),
                 ctx.accounts.stake_program.to_account_info(),
             ],
-            &[&[
-                b"pool_manager".as_ref(),
-                &ctx.accounts.project.key().to_bytes(),
-                &[bump],
-            ]],
+            &[&[b"pool_manager".as_ref(), project_key.as_ref(), &[bump]]],
+        )?;
+
+        let validator_allocation = &mut ctx.accounts.validator_allocation;
+        validator_allocation.init(project_key, ctx.bumps.validator_allocation)?;
+        validator_allocation.add(
+            ctx.accounts.phase_validator.key(),
+            ValidatorAllocation::DEFAULT_WEIGHT,
         )?;
 
         Ok(())
@@ -134,548 +174,5154 @@ pub mod ideapad_programs {
         reward_type: RewardType,
         cost: u64,
         quantity: Option<u32>,
-        bump: u8,
+        name: String,
+        symbol: String,
+        uri: String,
     ) -> Result<()> {
         ctx.accounts.contribution_reward.init(
             reward_type,
             ctx.accounts.project.key(),
+            ctx.accounts.project.contribution_reward_count,
             ctx.accounts.reward_collection_mint.key(),
             cost,
             quantity,
-            bump,
+            name,
+            symbol,
+            uri,
+            ctx.bumps.contribution_reward,
         )?;
 
-        Ok(())
-    }
-
-    // TODO add deposit authority so we can gate deposit through our program
-
-    /*
-        Deposits sol into validator, mints lst to program owned account. Mints Nft for redeeming amount to user.
-        Lst yeild is sent to sent to the a projects token account not owned by the program.
-     */
-    pub fn deposit_sol<'info>(ctx: Context<DepositSol>) -> Result<()> {
-        let stake_pool = ctx.accounts.stake_pool.key();
-        let reserve_stake_account = ctx.accounts.reserve_stake_account.key();
-        let manager_account = ctx.accounts.manager_account.key();
-        let pool_mint = ctx.accounts.pool_mint.key();
-        let stake_pool_withdrawal_authority = ctx.accounts.stake_pool_withdrawal_authority.key();
-
-        let instruction = spl_stake_pool::instruction::deposit_sol(
-            &spl_stake_pool::id(),
-            &stake_pool,
-            &stake_pool_withdrawal_authority,
-            &reserve_stake_account,
-            &ctx.accounts.payer.key(),
-            &ctx.accounts.lst_token_account.key(),
-            &manager_account,
-            &ctx.accounts.lst_token_account.key(),
-            &pool_mint,
-            &ctx.accounts.token_program.key(),
-            ctx.accounts.contribution_reward.cost,
-        );
-
-        let accounts = [
-            ctx.accounts.stake_pool.to_account_info(),
-            ctx.accounts
-                .stake_pool_withdrawal_authority
-                .to_account_info(),
-            ctx.accounts.reserve_stake_account.to_account_info(),
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.lst_token_account.to_account_info(),
-            ctx.accounts.project_fee_account.to_account_info(),
-            ctx.accounts.project_fee_account.to_account_info(),
-            ctx.accounts.pool_mint.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.token_program.to_account_info()
-        ];
+        ctx.accounts.project.increment_contribution_reward_count()?;
 
-        invoke(&instruction, &accounts)?;
+        // The project pda is mint and update authority of the collection receipts are verified into
+        let project_bump = [ctx.accounts.project.bump];
+        let signers: &[&[&[u8]]] = &[&[
+            b"project".as_ref(),
+            ctx.accounts.project.seed.as_slice(),
+            &project_bump,
+        ]];
 
         let mint_to_context = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             anchor_spl::token::MintTo {
-                mint: ctx.accounts.nft_mint.to_account_info(),
-                to: ctx.accounts.nft_token_account.to_account_info(),
-                authority: ctx.accounts.warp.to_account_info(),
+                mint: ctx.accounts.reward_collection_mint.to_account_info(),
+                to: ctx
+                    .accounts
+                    .reward_collection_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
             },
-            &signers,
+            signers,
         );
-    
+
         anchor_spl::token::mint_to(mint_to_context, 1)?;
-    
+
         let create_metadata_context = CpiContext::new_with_signer(
             ctx.accounts.token_metadata.to_account_info(),
             CreateMetadataAccountsV3 {
-                metadata: ctx.accounts.nft_metadata.to_account_info(),
-                mint: ctx.accounts.nft_mint.to_account_info(),
-                mint_authority: ctx.accounts.warp.to_account_info(),
+                metadata: ctx.accounts.collection_metadata.to_account_info(),
+                mint: ctx.accounts.reward_collection_mint.to_account_info(),
+                mint_authority: ctx.accounts.project.to_account_info(),
                 payer: ctx.accounts.payer.to_account_info(),
-                update_authority: ctx.accounts.warp.to_account_info(),
+                update_authority: ctx.accounts.project.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
                 rent: ctx.accounts.rent.to_account_info(),
             },
-            &signers,
+            signers,
         );
-    
-        let uri = ctx
-            .accounts
-            .warp
-            .nft_uri
-            .clone()
-            .unwrap_or(ctx.accounts.collection_metadata.data.uri.clone());
-    
+
+        let contribution_reward = &ctx.accounts.contribution_reward;
         anchor_spl::metadata::create_metadata_accounts_v3(
             create_metadata_context,
             DataV2 {
-                name: ctx.accounts.collection_metadata.data.name.clone(),
-                symbol: ctx.accounts.collection_metadata.data.symbol.clone(),
-                uri,
-                seller_fee_basis_points: ctx
-                    .accounts
-                    .collection_metadata
-                    .data
-                    .seller_fee_basis_points,
-                creators: Some(vec![MetadataCreator::default().into()]),
+                name: contribution_reward.name.clone(),
+                symbol: contribution_reward.symbol.clone(),
+                uri: contribution_reward.uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: None,
                 collection: None,
                 uses: None,
             },
             true,
             true,
-            None,
+            Some(CollectionDetails::V1 { size: 0 }),
         )?;
-    
+
         let create_master_edition_context = CpiContext::new_with_signer(
             ctx.accounts.token_metadata.to_account_info(),
             CreateMasterEditionV3 {
-                metadata: ctx.accounts.nft_metadata.to_account_info(),
-                mint: ctx.accounts.nft_mint.to_account_info(),
-                mint_authority: ctx.accounts.warp.to_account_info(),
+                metadata: ctx.accounts.collection_metadata.to_account_info(),
+                mint: ctx.accounts.reward_collection_mint.to_account_info(),
+                mint_authority: ctx.accounts.project.to_account_info(),
                 payer: ctx.accounts.payer.to_account_info(),
-                update_authority: ctx.accounts.warp.to_account_info(),
+                update_authority: ctx.accounts.project.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
                 rent: ctx.accounts.rent.to_account_info(),
-                edition: ctx.accounts.nft_master_edition.to_account_info(),
+                edition: ctx.accounts.collection_master_edition.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
             },
-            &signers,
+            signers,
         );
-    
+
         anchor_spl::metadata::create_master_edition_v3(create_master_edition_context, Some(0))?;
-    
-        let set_and_verify_context = CpiContext::new_with_signer(
-            ctx.accounts.token_metadata.to_account_info(),
-            SetAndVerifySizedCollectionItem {
-                metadata: ctx.accounts.nft_metadata.to_account_info(),
-                collection_authority: ctx.accounts.warp.to_account_info(),
-                payer: ctx.accounts.payer.to_account_info(),
-                update_authority: ctx.accounts.warp.to_account_info(),
-                collection_mint: ctx.accounts.collection_mint.to_account_info(),
-                collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
-                collection_master_edition: ctx.accounts.collection_master_edition.to_account_info(),
-            },
-            &signers,
-        );
-    
-        anchor_spl::metadata::set_and_verify_sized_collection_item(set_and_verify_context, None)?;
 
         Ok(())
     }
 
-    pub fn change_state<'info>(ctx: Context<ChangeState>, state: ProjectState) -> Result<()> {
-        ctx.accounts.project.state = state;
+    // Quantity can be raised (or made unlimited) but never set below what has already been sold.
+    pub fn update_contribution_reward_quantity(
+        ctx: Context<UpdateContributionReward>,
+        quantity: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            matches!(
+                ctx.accounts.project.state,
+                ProjectState::Draft | ProjectState::Raising
+            ),
+            IdeaPadErrorCode::RewardLocked
+        );
 
-        Ok(())
-    }
+        // Backers have bought against the cap once raising, so it can only go up
+        if ctx.accounts.project.state == ProjectState::Raising {
+            require!(
+                ctx.accounts.contribution_reward.raises_quantity(quantity),
+                IdeaPadErrorCode::QuantityCanOnlyIncrease
+            );
+        }
 
-    // Return LST to user that they can either continue to hold and support creator or 
-    // they can convert to sol from stakepool.
-    pub fn claim_back<'info>(ctx: Context<ClaimBack>) -> Result<()> {
+        ctx.accounts.contribution_reward.set_quantity(quantity)?;
 
         Ok(())
     }
 
+    // View of the reward tiers a backer is entitled to, returned as reward indices.
+    // Remaining accounts are every contribution reward of the project in index order, followed by
+    // (receipt token account, receipt metadata) pairs for the receipts held by the backer.
+    pub fn get_entitlements<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetEntitlements<'info>>,
+    ) -> Result<Vec<u16>> {
+        let project_key = ctx.accounts.project.key();
+        let reward_count = ctx.accounts.project.contribution_reward_count as usize;
 
-}
+        require!(
+            ctx.remaining_accounts.len() >= reward_count,
+            IdeaPadErrorCode::InvalidEntitlementAccounts
+        );
 
-#[derive(Accounts)]
-#[instruction(
-    seed: Vec<u8>
-)]
-pub struct CreateProject<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        let (reward_accounts, receipt_accounts) = ctx.remaining_accounts.split_at(reward_count);
+        let receipts = receipt_accounts.chunks_exact(2);
+        require!(
+            receipts.remainder().is_empty(),
+            IdeaPadErrorCode::InvalidEntitlementAccounts
+        );
 
-    pub authority: Signer<'info>,
+        let rewards = ContributionReward::load_all(&project_key, reward_accounts)?;
 
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"project", seed.as_slice()],
-        bump,
-        space = Project::space(&seed)
-    )]
-    pub project: Account<'info, Project>,
-    #[account(
-        init, 
-        payer = payer,
-        seeds = [b"pool_manager", project.key().as_ref()],
-        bump,
-        space = PoolManager::space()
-    )]
-    pub stake_pool_manager: Account<'info, PoolManager>,
+        let mut held = Vec::new();
+        for receipt in receipts {
+            let token_account = Account::<TokenAccount>::try_from(&receipt[0])?;
+            let metadata = Account::<MetadataAccount>::try_from(&receipt[1])?;
 
-    #[account(
-        // mint::authority = Pubkey::from_str("bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1").unwrap()
-    )]
-    pub pool_mint: Account<'info, Mint>,
+            require!(
+                token_account.owner == ctx.accounts.backer.key() && token_account.amount == 1,
+                IdeaPadErrorCode::ReceiptNotHeld
+            );
+            require!(
+                metadata.mint == token_account.mint,
+                IdeaPadErrorCode::ReceiptNotFromProject
+            );
 
-    pub stake_pool: AccountInfo<'info>,
-    pub reserve_stake: AccountInfo<'info>,
+            let reward = metadata
+                .collection
+                .as_ref()
+                .filter(|collection| collection.verified)
+                .and_then(|collection| {
+                    rewards
+                        .iter()
+                        .find(|reward| reward.reward_collection_mint == collection.key)
+                })
+                .ok_or(IdeaPadErrorCode::ReceiptNotFromProject)?;
 
-    pub project_fee_account: Account<'info, TokenAccount>,
+            held.push(reward.index);
+        }
 
-    /// Restrict this to a list we control
-    pub validator_list: AccountInfo<'info>,
+        Ok(ContributionReward::entitlements(&rewards, &held))
+    }
 
-    pub reserve_stake_account: AccountInfo<'info>,
-    pub manager_account: AccountInfo<'info>,
-    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    // Backer presents a receipt to claim its reward once the project is funded. Each receipt can
+    // only be redeemed once as the redemption pda is seeded by the receipt mint, and the receipt
+    // record is marked redeemed so its deposit can't be withdrawn early or minted to a new receipt
+    // that redeems again.
+    pub fn redeem_reward(ctx: Context<RedeemReward>) -> Result<()> {
+        require!(
+            matches!(
+                ctx.accounts.project.state,
+                ProjectState::Funded | ProjectState::Complete
+            ),
+            IdeaPadErrorCode::RewardNotRedeemable
+        );
 
-    pub stake_account: AccountInfo<'info>,
-    pub phase_validator: AccountInfo<'info>,
+        let now = Clock::get()?.unix_timestamp;
 
-    pub stake_pool_program: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+        ctx.accounts.reward_redemption.init(
+            ctx.accounts.contribution_reward.key(),
+            ctx.accounts.backer.key(),
+            ctx.accounts.nft_mint.key(),
+            now,
+            ctx.bumps.reward_redemption,
+        )?;
+        ctx.accounts.receipt.redeemed = true;
 
-    pub rent: Sysvar<'info, Rent>,
-    pub clock: Sysvar<'info, Clock>,
-    pub stake_history: Sysvar<'info, StakeHistory>,
-    pub stake_config: AccountInfo<'info>,
-    pub stake_program: AccountInfo<'info>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct CreateContributionReward<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    // Creator records that a redeemed reward has been delivered.
+    pub fn mark_reward_fulfilled(ctx: Context<MarkRewardFulfilled>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
-    pub authority: Signer<'info>,
+        ctx.accounts.reward_redemption.fulfill(now)?;
 
-    #[account(
-        mut,
-        has_one = authority
-    )]
-    pub project: Account<'info, Project>,
+        Ok(())
+    }
 
-    #[account(
-        init,
-        payer = payer,
-        seeds = [b"reward", project.key().as_ref(), &[project.contribution_reward_count]],
-        bump,
-        space = ContributionReward::space()
-    )]
-    pub contribution_reward: Account<'info, ContributionReward>,
+    /*
+        Deposits sol into validator, mints lst to program owned account. Mints Nft for redeeming amount to user.
+        The amount can exceed the reward cost, anything above it is a tip to the project.
+        Lst yeild is sent to the projects fee account owned by the pool manager and paid out with harvest_yield.
+     */
+    pub fn deposit_sol(ctx: Context<PoolDeposit>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit(&ctx.bumps, amount, |accounts| {
+            let instruction = spl_stake_pool::instruction::deposit_sol_with_authority(
+                &spl_stake_pool::id(),
+                &accounts.stake_pool.key(),
+                &accounts.deposit_authority.key(),
+                &accounts.stake_pool_withdrawal_authority.key(),
+                &accounts.reserve_stake_account.key(),
+                &accounts.mint_receipt.payer.key(),
+                &accounts.lst_token_account.key(),
+                &accounts.project_fee_account.key(),
+                // No referral fee is configured, the fee account only stands in as referrer
+                &accounts.project_fee_account.key(),
+                &accounts.pool_mint.key(),
+                &accounts.token_program.key(),
+                amount,
+            );
 
-    #[account(
-        init,
-        payer = payer,
-        mint::decimals = 0,
-        mint::authority = project,
-        mint::freeze_authority = project
-    )]
-    pub reward_collection_mint: Account<'info, Mint>,
+            invoke_signed(
+                &instruction,
+                &[
+                    accounts.stake_pool.to_account_info(),
+                    accounts.stake_pool_withdrawal_authority.to_account_info(),
+                    accounts.reserve_stake_account.to_account_info(),
+                    accounts.mint_receipt.payer.to_account_info(),
+                    accounts.lst_token_account.to_account_info(),
+                    accounts.project_fee_account.to_account_info(),
+                    accounts.pool_mint.to_account_info(),
+                    accounts.system_program.to_account_info(),
+                    accounts.token_program.to_account_info(),
+                    accounts.deposit_authority.to_account_info(),
+                    accounts.stake_pool_program.to_account_info(),
+                ],
+                &[&[
+                    b"deposit_authority".as_ref(),
+                    accounts.project.key().as_ref(),
+                    &[ctx.bumps.deposit_authority],
+                ]],
+            )?;
 
-    #[account(
-        init,
-        payer = payer,
-        associated_token::mint = reward_collection_mint,
-        associated_token::authority = authority
-    )]
-    pub reward_collection_token_account: Box<Account<'info, TokenAccount>>,
+            Ok(())
+        })
+    }
 
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    // Deposits a delegated stake account into the project pool, valued at its delegation, and mints
+    // the same receipt as deposit_sol.
+    pub fn deposit_stake(ctx: Context<DepositStake>) -> Result<()> {
+        // Only the delegated stake is credited, the rent exempt reserve is not a contribution
+        let amount =
+            StakeStateV2::deserialize(&mut &ctx.accounts.deposit_stake.try_borrow_data()?[..])?
+                .delegation()
+                .ok_or(IdeaPadErrorCode::StakeNotDelegated)?
+                .stake;
 
-    pub token_program: Program<'info, Token>,
-    pub token_metadata: Program<'info, Metadata>,
-    pub system_program: Program<'info, System>,
-}
+        ctx.accounts
+            .pool_deposit
+            .deposit(&ctx.bumps.pool_deposit, amount, |accounts| {
+                // Authorizes the stake account to the pool deposit authority, then deposits it
+                let instructions = spl_stake_pool::instruction::deposit_stake_with_authority(
+                    &spl_stake_pool::id(),
+                    &accounts.stake_pool.key(),
+                    &ctx.accounts.validator_list.key(),
+                    &accounts.deposit_authority.key(),
+                    &accounts.stake_pool_withdrawal_authority.key(),
+                    &ctx.accounts.deposit_stake.key(),
+                    &accounts.mint_receipt.owner.key(),
+                    &ctx.accounts.validator_stake_account.key(),
+                    &accounts.reserve_stake_account.key(),
+                    &accounts.lst_token_account.key(),
+                    &accounts.project_fee_account.key(),
+                    // No referral fee is configured, the fee account only stands in as referrer
+                    &accounts.project_fee_account.key(),
+                    &accounts.pool_mint.key(),
+                    &accounts.token_program.key(),
+                );
 
-#[derive(Accounts)]
-pub struct DepositSol<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+                let account_infos = [
+                    accounts.stake_pool.to_account_info(),
+                    ctx.accounts.validator_list.to_account_info(),
+                    accounts.deposit_authority.to_account_info(),
+                    accounts.stake_pool_withdrawal_authority.to_account_info(),
+                    ctx.accounts.deposit_stake.to_account_info(),
+                    accounts.mint_receipt.owner.to_account_info(),
+                    ctx.accounts.validator_stake_account.to_account_info(),
+                    accounts.reserve_stake_account.to_account_info(),
+                    accounts.lst_token_account.to_account_info(),
+                    accounts.project_fee_account.to_account_info(),
+                    accounts.pool_mint.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    accounts.token_program.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    accounts.stake_pool_program.to_account_info(),
+                ];
 
-    pub wallet: Signer<'info>,
+                let project_key = accounts.project.key();
+                for instruction in instructions {
+                    invoke_signed(
+                        &instruction,
+                        &account_infos,
+                        &[&[
+                            b"deposit_authority".as_ref(),
+                            project_key.as_ref(),
+                            &[ctx.bumps.pool_deposit.deposit_authority],
+                        ]],
+                    )?;
+                }
 
-    #[account(
-        mut
-    )]
-    pub project: Account<'info, Project>,
+                Ok(())
+            })
+    }
 
-    #[account(
-        has_one = project,
-        has_one = reward_collection_mint,
-    )]
-    pub contribution_reward: Account<'info, ContributionReward>,
+    pub fn change_state(ctx: Context<ChangeState>, state: ProjectState) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
-    #[account(
-        mint::authority = project,
-        mint::freeze_authority = project
-    )]
-    pub reward_collection_mint: Account<'info, Mint>,
+        // Funded and Failed are only reachable through resolve_raise
+        require!(
+            state != ProjectState::Funded && state != ProjectState::Failed,
+            IdeaPadErrorCode::RaiseNotResolvable
+        );
 
-    #[account(
-        init,
-        payer = payer,
-        associated_token::mint = reward_collection_mint,
-        associated_token::authority = wallet
-    )]
-    pub reward_token_account: Box<Account<'info, TokenAccount>>,
+        ctx.accounts.project.transition(state, now)?;
 
-    #[account(
-        init,
-        payer = payer,
-        mint::decimals = 0,
-        mint::authority = contribution_reward,
-        mint::freeze_authority = contribution_reward
-    )]
-    pub nft_mint: Box<Account<'info, Mint>>,
+        Ok(())
+    }
 
-    /// CHECK inside instruction
-    #[account(
-        mut,
-        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
-        bump,
-        seeds::program = Metadata::id(),
-        constraint = nft_metadata.collection.clone().unwrap().key == contribution_reward.reward_collection_mint,
-        constraint = nft_metadata.collection.clone().unwrap().verified == true
-    )]
-    pub nft_metadata: Account<'info, MetadataAccount>,
+    // Permissionless, moves a Raising project to Funded or Failed once the raise has ended.
+    pub fn resolve_raise(ctx: Context<ResolveRaise>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
+        ctx.accounts.project.resolve_raise(now)?;
 
-    /// CHECK inside instruction
-    #[account(
-        mut,
-        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref(), b"edition"],
-        bump,
-        seeds::program = Metadata::id()
-    )]
-    pub nft_master_edition: AccountInfo<'info>,
+        Ok(())
+    }
 
-    pub stake_vault: Account<'info, StakeVault>,
+    // Return LST to user that they can either continue to hold and support creator or
+    // they can convert to sol from stakepool, claim_back_as_sol does both in one step.
+    // The receipt nft is the claim key, whoever holds it is paid the lst of its deposit and the
+    // receipt is burned.
+    pub fn claim_back(ctx: Context<ClaimBack>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
-    pub project_fee_account: Account<'info, TokenAccount>,
+        require!(
+            ctx.accounts.project.is_redeemable(now),
+            IdeaPadErrorCode::ClaimNotAvailable
+        );
 
-    #[account(
-        init,
-        payer = payer,
-        associated_token::mint = pool_mint,
-        associated_token::authority = stake_vault
-    )]
-    pub lst_token_account: Account<'info, TokenAccount>,
+        let project_key = ctx.accounts.project.key();
+        let staker_key = ctx.accounts.stake_vault.staker;
+        let signers: &[&[&[u8]]] = &[&[
+            b"stake_vault".as_ref(),
+            project_key.as_ref(),
+            staker_key.as_ref(),
+            &[ctx.accounts.stake_vault.bump],
+        ]];
 
-    #[account(
-        address = project.lst_mint 
-    )]
-    pub pool_mint: Account<'info, Mint>,
+        let claim_amount = ctx.accounts.receipt.lst_amount;
 
-    pub stake_pool: AccountInfo<'info>,
+        let transfer_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::Transfer {
+                from: ctx.accounts.lst_token_account.to_account_info(),
+                to: ctx.accounts.holder_lst_token_account.to_account_info(),
+                authority: ctx.accounts.stake_vault.to_account_info(),
+            },
+            signers,
+        );
 
-    pub reserve_stake_account: AccountInfo<'info>,
-    pub manager_account: AccountInfo<'info>,
-    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+        anchor_spl::token::transfer(transfer_context, claim_amount)?;
 
-    pub associated_token_program: Program<'info, AssociatedToken>,
+        burn_receipt(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.nft_token_account.to_account_info(),
+            ctx.accounts.holder.to_account_info(),
+        )?;
 
-    pub token_program: Program<'info, Token>,
-    pub token_metadata: Program<'info, Metadata>,
-    pub system_program: Program<'info, System>,
-}
+        let stake_vault = &mut ctx.accounts.stake_vault;
+        stake_vault.record_withdrawal(ctx.accounts.receipt.lamports, claim_amount)?;
+        stake_vault.is_claimed = stake_vault.lst_received == 0;
 
-#[derive(Accounts)]
-pub struct ChangeState<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        Ok(())
+    }
 
-    pub authority: Signer<'info>,
+    // Claims the receipt lst as sol in one step. The lst is withdrawn from the reserve, or when the
+    // reserve is thin split from a validator stake account into a fresh stake account the holder
+    // created for it. The pool rejects the withdrawal below minimum_lamports.
+    pub fn claim_back_as_sol(
+        ctx: Context<ClaimBackAsSol>,
+        minimum_lamports: u64,
+        split_stake: bool,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
-    #[account(
-        has_one = authority,
-        seeds = [b"project", project.seed.as_slice()],
-        bump = project.bump
-    )]
-    pub project: Account<'info, Project>,
+        require!(
+            ctx.accounts.project.is_redeemable(now),
+            IdeaPadErrorCode::ClaimNotAvailable
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        let project_key = ctx.accounts.project.key();
+        let staker_key = ctx.accounts.stake_vault.staker;
+        let signers: &[&[&[u8]]] = &[&[
+            b"stake_vault".as_ref(),
+            project_key.as_ref(),
+            staker_key.as_ref(),
+            &[ctx.accounts.stake_vault.bump],
+        ]];
 
+        let claim_amount = ctx.accounts.receipt.lst_amount;
 
-#[derive(Accounts)]
-pub struct ClaimBack<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        if split_stake {
+            let (Some(stake_to_split), Some(stake_to_receive)) = (
+                ctx.accounts.stake_to_split.as_ref(),
+                ctx.accounts.stake_to_receive.as_ref(),
+            ) else {
+                return err!(IdeaPadErrorCode::MissingStakeAccounts);
+            };
 
-    pub authority: Signer<'info>,
+            let withdraw_ix = spl_stake_pool::instruction::withdraw_stake_with_slippage(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.validator_list.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &stake_to_split.key(),
+                &stake_to_receive.key(),
+                &ctx.accounts.holder.key(),
+                &ctx.accounts.stake_vault.key(),
+                &ctx.accounts.lst_token_account.key(),
+                &ctx.accounts.project_fee_account.key(),
+                &ctx.accounts.pool_mint.key(),
+                &ctx.accounts.token_program.key(),
+                claim_amount,
+                minimum_lamports,
+            );
+
+            invoke_signed(
+                &withdraw_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts.validator_list.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    stake_to_split.to_account_info(),
+                    stake_to_receive.to_account_info(),
+                    ctx.accounts.holder.to_account_info(),
+                    ctx.accounts.stake_vault.to_account_info(),
+                    ctx.accounts.lst_token_account.to_account_info(),
+                    ctx.accounts.project_fee_account.to_account_info(),
+                    ctx.accounts.pool_mint.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        } else {
+            let withdraw_ix = spl_stake_pool::instruction::withdraw_sol_with_slippage(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &ctx.accounts.stake_vault.key(),
+                &ctx.accounts.lst_token_account.key(),
+                &ctx.accounts.reserve_stake.key(),
+                &ctx.accounts.holder.key(),
+                &ctx.accounts.project_fee_account.key(),
+                &ctx.accounts.pool_mint.key(),
+                &ctx.accounts.token_program.key(),
+                claim_amount,
+                minimum_lamports,
+            );
+
+            invoke_signed(
+                &withdraw_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    ctx.accounts.stake_vault.to_account_info(),
+                    ctx.accounts.lst_token_account.to_account_info(),
+                    ctx.accounts.reserve_stake.to_account_info(),
+                    ctx.accounts.holder.to_account_info(),
+                    ctx.accounts.project_fee_account.to_account_info(),
+                    ctx.accounts.pool_mint.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        }
+
+        burn_receipt(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.nft_token_account.to_account_info(),
+            ctx.accounts.holder.to_account_info(),
+        )?;
+
+        let stake_vault = &mut ctx.accounts.stake_vault;
+        stake_vault.record_withdrawal(ctx.accounts.receipt.lamports, claim_amount)?;
+        stake_vault.is_claimed = stake_vault.lst_received == 0;
+
+        Ok(())
+    }
+
+    // Only zero by default, the penalty is taken from early withdrawals and left to the project.
+    pub fn set_early_exit_penalty(
+        ctx: Context<SetEarlyExitPenalty>,
+        penalty: FeeRate,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.project.state == ProjectState::Draft,
+            IdeaPadErrorCode::ProjectConfigLocked
+        );
+
+        ctx.accounts.project.set_early_exit_penalty(penalty)?;
+
+        Ok(())
+    }
+
+    // Withdraws receipt lst from the stake vault before the project is redeemable. The early exit
+    // penalty goes to the project fee account and is paid out as creator yield. When the remaining
+    // deposit no longer covers the receipt tier it is burned, and the highest tier still covered
+    // can be minted to the holder with claim_downgraded_receipt. Remaining accounts are every
+    // contribution reward of the project in index order, writable as the sale of the tier
+    // downgraded to is recorded here.
+    pub fn early_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EarlyWithdraw<'info>>,
+        lst_amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            matches!(
+                ctx.accounts.project.state,
+                ProjectState::Raising | ProjectState::Funded
+            ) && !ctx.accounts.project.is_redeemable(now),
+            IdeaPadErrorCode::EarlyWithdrawNotAvailable
+        );
+        require!(
+            lst_amount > 0 && lst_amount <= ctx.accounts.receipt.lst_amount,
+            IdeaPadErrorCode::InvalidWithdrawAmount
+        );
+
+        let penalty_amount = ctx
+            .accounts
+            .project
+            .early_exit_penalty()
+            .apply(lst_amount)?;
+        let holder_amount = lst_amount
+            .checked_sub(penalty_amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        let project_key = ctx.accounts.project.key();
+        let staker_key = ctx.accounts.stake_vault.staker;
+        let signers: &[&[&[u8]]] = &[&[
+            b"stake_vault".as_ref(),
+            project_key.as_ref(),
+            staker_key.as_ref(),
+            &[ctx.accounts.stake_vault.bump],
+        ]];
+
+        if penalty_amount > 0 {
+            let transfer_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.lst_token_account.to_account_info(),
+                    to: ctx.accounts.project_fee_account.to_account_info(),
+                    authority: ctx.accounts.stake_vault.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::transfer(transfer_context, penalty_amount)?;
+        }
+
+        let transfer_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::Transfer {
+                from: ctx.accounts.lst_token_account.to_account_info(),
+                to: ctx.accounts.holder_lst_token_account.to_account_info(),
+                authority: ctx.accounts.stake_vault.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::token::transfer(transfer_context, holder_amount)?;
+
+        let withdrawn_lamports = ctx.accounts.receipt.record_withdrawal(lst_amount)?;
+        ctx.accounts
+            .stake_vault
+            .record_withdrawal(withdrawn_lamports, lst_amount)?;
+        ctx.accounts.project.record_withdrawal(withdrawn_lamports)?;
+
+        let remaining_lamports = ctx.accounts.receipt.lamports;
+        let fully_withdrawn = ctx.accounts.receipt.lst_amount == 0;
+        if fully_withdrawn || remaining_lamports < ctx.accounts.contribution_reward.cost {
+            burn_receipt(
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.nft_mint.to_account_info(),
+                ctx.accounts.nft_token_account.to_account_info(),
+                ctx.accounts.holder.to_account_info(),
+            )?;
+
+            ctx.accounts.contribution_reward.release_sale()?;
+
+            if fully_withdrawn {
+                ctx.accounts
+                    .receipt
+                    .close(ctx.accounts.holder.to_account_info())?;
+            } else {
+                let rewards = ContributionReward::load_all(&project_key, ctx.remaining_accounts)?;
+                require!(
+                    rewards.len() == ctx.accounts.project.contribution_reward_count as usize,
+                    IdeaPadErrorCode::InvalidRewardAccounts
+                );
+
+                // Lst left without a receipt could never be claimed
+                let reward_index =
+                    ContributionReward::highest_affordable(&rewards, remaining_lamports)
+                        .ok_or(IdeaPadErrorCode::WithdrawBelowLowestTier)?;
+
+                // Reserved now, as the tier could sell out before the receipt is claimed
+                let mut downgraded_reward = Account::<ContributionReward>::try_from(
+                    &ctx.remaining_accounts[reward_index as usize],
+                )?;
+                downgraded_reward.record_sale()?;
+                downgraded_reward.exit(&crate::ID)?;
+
+                ctx.accounts.receipt.pending = Some(PendingReceipt {
+                    reward_index,
+                    holder: ctx.accounts.holder.key(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Mints the receipt of the tier a position was downgraded to by early_withdraw, carrying over
+    // the remaining deposit to a new receipt record.
+    pub fn claim_downgraded_receipt(ctx: Context<ClaimDowngradedReceipt>) -> Result<()> {
+        let pending = ctx
+            .accounts
+            .downgraded_receipt
+            .pending
+            .ok_or(IdeaPadErrorCode::NoPendingReceipt)?;
+
+        let mint_receipt = &mut ctx.accounts.mint_receipt;
+        require!(
+            pending.reward_index == mint_receipt.contribution_reward.index,
+            IdeaPadErrorCode::NoPendingReceipt
+        );
+        require!(
+            pending.holder == mint_receipt.owner.key(),
+            IdeaPadErrorCode::NotPendingReceiptHolder
+        );
+
+        // The sale was recorded by early_withdraw, so a sold out tier still mints
+        let downgraded_receipt = &ctx.accounts.downgraded_receipt;
+        mint_receipt.receipt.init(
+            downgraded_receipt.project,
+            downgraded_receipt.stake_vault,
+            mint_receipt.contribution_reward.key(),
+            mint_receipt.nft_mint.key(),
+            downgraded_receipt.lamports,
+            downgraded_receipt.lst_amount,
+            ctx.bumps.mint_receipt.receipt,
+        )?;
+        mint_receipt.receipt.redeemed = downgraded_receipt.redeemed;
+
+        mint_receipt.mint(&ctx.accounts.project)?;
+
+        ctx.accounts
+            .downgraded_receipt
+            .close(ctx.accounts.mint_receipt.owner.to_account_info())?;
+
+        Ok(())
+    }
+
+    // Returns the stake of a receipt from a Failed or Cancelled project to its holder, as lst or as
+    // sol through the pool. The receipt nft is burned, and once every receipt of the stake vault is
+    // refunded the vault is closed back to the staker.
+    pub fn refund(ctx: Context<Refund>, withdraw_as_sol: bool) -> Result<()> {
+        require!(
+            ctx.accounts.project.is_refundable(),
+            IdeaPadErrorCode::RefundNotAvailable
+        );
+
+        let project_key = ctx.accounts.project.key();
+        let staker_key = ctx.accounts.stake_vault.staker;
+        let signers: &[&[&[u8]]] = &[&[
+            b"stake_vault".as_ref(),
+            project_key.as_ref(),
+            staker_key.as_ref(),
+            &[ctx.accounts.stake_vault.bump],
+        ]];
+
+        let refund_amount = ctx.accounts.receipt.lst_amount;
+
+        if withdraw_as_sol {
+            let withdraw_ix = spl_stake_pool::instruction::withdraw_sol(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &ctx.accounts.stake_vault.key(),
+                &ctx.accounts.lst_token_account.key(),
+                &ctx.accounts.reserve_stake.key(),
+                &ctx.accounts.holder.key(),
+                &ctx.accounts.project_fee_account.key(),
+                &ctx.accounts.pool_mint.key(),
+                &ctx.accounts.token_program.key(),
+                refund_amount,
+            );
+
+            invoke_signed(
+                &withdraw_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    ctx.accounts.stake_vault.to_account_info(),
+                    ctx.accounts.lst_token_account.to_account_info(),
+                    ctx.accounts.reserve_stake.to_account_info(),
+                    ctx.accounts.holder.to_account_info(),
+                    ctx.accounts.project_fee_account.to_account_info(),
+                    ctx.accounts.pool_mint.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        } else {
+            let holder_lst_token_account = ctx
+                .accounts
+                .holder_lst_token_account
+                .as_ref()
+                .ok_or(IdeaPadErrorCode::MissingStakerLstAccount)?;
+
+            let transfer_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.lst_token_account.to_account_info(),
+                    to: holder_lst_token_account.to_account_info(),
+                    authority: ctx.accounts.stake_vault.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::transfer(transfer_context, refund_amount)?;
+        }
+
+        burn_receipt(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.nft_token_account.to_account_info(),
+            ctx.accounts.holder.to_account_info(),
+        )?;
+
+        ctx.accounts
+            .stake_vault
+            .record_withdrawal(ctx.accounts.receipt.lamports, refund_amount)?;
+
+        // Lst anyone sent to the vault account would make closing it fail, so it is left open then
+        ctx.accounts.lst_token_account.reload()?;
+        if ctx.accounts.stake_vault.lst_received == 0 && ctx.accounts.lst_token_account.amount == 0
+        {
+            let close_lst_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::CloseAccount {
+                    account: ctx.accounts.lst_token_account.to_account_info(),
+                    destination: ctx.accounts.staker.to_account_info(),
+                    authority: ctx.accounts.stake_vault.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::close_account(close_lst_context)?;
+
+            ctx.accounts
+                .stake_vault
+                .close(ctx.accounts.staker.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    // Pays out the fee lst the pool mints to the project fee account, less the protocol share.
+    pub fn harvest_yield(ctx: Context<HarvestYield>, withdraw_as_sol: bool) -> Result<()> {
+        let project_key = ctx.accounts.project.key();
+        let signers: &[&[&[u8]]] = &[&[
+            b"pool_manager".as_ref(),
+            project_key.as_ref(),
+            &[ctx.accounts.stake_pool_manager.bump],
+        ]];
+
+        let update_balance_ix = spl_stake_pool::instruction::update_stake_pool_balance(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.validator_list.key(),
+            &ctx.accounts.reserve_stake.key(),
+            &ctx.accounts.project_fee_account.key(),
+            &ctx.accounts.pool_mint.key(),
+            &ctx.accounts.token_program.key(),
+        );
+
+        invoke_signed(
+            &update_balance_ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts
+                    .stake_pool_withdrawal_authority
+                    .to_account_info(),
+                ctx.accounts.validator_list.to_account_info(),
+                ctx.accounts.reserve_stake.to_account_info(),
+                ctx.accounts.project_fee_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            &[],
+        )?;
+
+        ctx.accounts.project_fee_account.reload()?;
+
+        let harvested = ctx.accounts.project_fee_account.amount;
+        require!(harvested > 0, IdeaPadErrorCode::NothingToHarvest);
+
+        let (protocol_amount, creator_amount) = ctx
+            .accounts
+            .protocol_config
+            .split_harvest(harvested, ctx.accounts.project.crank_tips)?;
+
+        if protocol_amount > 0 {
+            let transfer_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.project_fee_account.to_account_info(),
+                    to: ctx.accounts.treasury_lst_account.to_account_info(),
+                    authority: ctx.accounts.stake_pool_manager.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::transfer(transfer_context, protocol_amount)?;
+        }
+
+        if withdraw_as_sol {
+            let withdraw_ix = spl_stake_pool::instruction::withdraw_sol(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &ctx.accounts.stake_pool_manager.key(),
+                &ctx.accounts.project_fee_account.key(),
+                &ctx.accounts.reserve_stake.key(),
+                &ctx.accounts.authority.key(),
+                &ctx.accounts.project_fee_account.key(),
+                &ctx.accounts.pool_mint.key(),
+                &ctx.accounts.token_program.key(),
+                creator_amount,
+            );
+
+            invoke_signed(
+                &withdraw_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    ctx.accounts.stake_pool_manager.to_account_info(),
+                    ctx.accounts.project_fee_account.to_account_info(),
+                    ctx.accounts.reserve_stake.to_account_info(),
+                    ctx.accounts.authority.to_account_info(),
+                    ctx.accounts.project_fee_account.to_account_info(),
+                    ctx.accounts.pool_mint.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        } else {
+            let creator_lst_account = ctx
+                .accounts
+                .creator_lst_account
+                .as_ref()
+                .ok_or(IdeaPadErrorCode::MissingCreatorLstAccount)?;
+
+            let transfer_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.project_fee_account.to_account_info(),
+                    to: creator_lst_account.to_account_info(),
+                    authority: ctx.accounts.stake_pool_manager.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::transfer(transfer_context, creator_amount)?;
+        }
+
+        ctx.accounts.project.record_harvest(harvested)?;
+
+        Ok(())
+    }
+
+    // Permissionless epoch update of a project pool. The first crank of an epoch on a raising or
+    // funded project with stake is tipped in pool lst, paid out of the protocol share of its fees.
+    pub fn crank_project_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankProjectPool<'info>>,
+    ) -> Result<()> {
+        let project_key = ctx.accounts.project.key();
+        let signers: &[&[&[u8]]] = &[&[
+            b"pool_manager".as_ref(),
+            project_key.as_ref(),
+            &[ctx.accounts.stake_pool_manager.bump],
+        ]];
+
+        let validator_list =
+            ValidatorList::deserialize(&mut &ctx.accounts.validator_list.try_borrow_data()?[..])?;
+        let vote_accounts: Vec<Pubkey> = validator_list
+            .validators
+            .iter()
+            .map(|validator| validator.vote_account_address)
+            .collect();
+
+        let update_validator_list_ix = spl_stake_pool::instruction::update_validator_list_balance(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.validator_list.key(),
+            &ctx.accounts.reserve_stake.key(),
+            &validator_list,
+            &vote_accounts,
+            0,
+            false,
+        );
+
+        // Validator and transient stake account pairs are passed as remaining accounts
+        let mut update_validator_list_accounts = vec![
+            ctx.accounts.stake_pool.to_account_info(),
+            ctx.accounts
+                .stake_pool_withdrawal_authority
+                .to_account_info(),
+            ctx.accounts.validator_list.to_account_info(),
+            ctx.accounts.reserve_stake.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.stake_pool_program.to_account_info(),
+        ];
+        update_validator_list_accounts.extend(ctx.remaining_accounts.iter().cloned());
+
+        invoke_signed(
+            &update_validator_list_ix,
+            &update_validator_list_accounts,
+            signers,
+        )?;
+
+        let update_balance_ix = spl_stake_pool::instruction::update_stake_pool_balance(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.validator_list.key(),
+            &ctx.accounts.reserve_stake.key(),
+            &ctx.accounts.project_fee_account.key(),
+            &ctx.accounts.pool_mint.key(),
+            &ctx.accounts.token_program.key(),
+        );
+
+        invoke_signed(
+            &update_balance_ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts
+                    .stake_pool_withdrawal_authority
+                    .to_account_info(),
+                ctx.accounts.validator_list.to_account_info(),
+                ctx.accounts.reserve_stake.to_account_info(),
+                ctx.accounts.project_fee_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            signers,
+        )?;
+
+        let cleanup_ix = spl_stake_pool::instruction::cleanup_removed_validator_entries(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.validator_list.key(),
+        );
+
+        invoke_signed(
+            &cleanup_ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.validator_list.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            signers,
+        )?;
+
+        let epoch = ctx.accounts.clock.epoch;
+        let first_crank_this_epoch = ctx.accounts.project.last_update_epoch < epoch;
+        ctx.accounts.project.last_update_epoch = epoch;
+
+        if let Some(cranker_lst_account) = ctx.accounts.cranker_lst_account.as_ref() {
+            if first_crank_this_epoch && ctx.accounts.project.pays_crank_tip() {
+                ctx.accounts.project_fee_account.reload()?;
+                let rate = PoolRate::load(&ctx.accounts.stake_pool, &ctx.accounts.pool_mint.key())?;
+                let tip = ctx.accounts.protocol_config.crank_tip_lst(
+                    &rate,
+                    ctx.accounts.project_fee_account.amount,
+                    ctx.accounts.project.crank_tips,
+                )?;
+
+                if tip > 0 {
+                    let transfer_context = CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        anchor_spl::token::Transfer {
+                            from: ctx.accounts.project_fee_account.to_account_info(),
+                            to: cranker_lst_account.to_account_info(),
+                            authority: ctx.accounts.stake_pool_manager.to_account_info(),
+                        },
+                        signers,
+                    );
+
+                    anchor_spl::token::transfer(transfer_context, tip)?;
+                    ctx.accounts.project.record_crank_tip(tip)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Delegates reserve lamports above the protocol buffer to a project validator up to its
+    // weighted target, or pulls stake back into the reserve when it has dropped below the buffer.
+    pub fn rebalance_project_stake(ctx: Context<RebalanceProjectStake>) -> Result<()> {
+        let project_key = ctx.accounts.project.key();
+        let signers: &[&[&[u8]]] = &[&[
+            b"pool_manager".as_ref(),
+            project_key.as_ref(),
+            &[ctx.accounts.stake_pool_manager.bump],
+        ]];
+
+        let validator_list =
+            ValidatorList::deserialize(&mut &ctx.accounts.validator_list.try_borrow_data()?[..])?;
+        let validator_stake_info = validator_list
+            .find(&ctx.accounts.validator.key())
+            .ok_or(IdeaPadErrorCode::ValidatorNotInPool)?;
+        require!(
+            ctx.accounts
+                .validator_allocation
+                .contains(&ctx.accounts.validator.key()),
+            IdeaPadErrorCode::ValidatorNotInPool
+        );
+        require!(
+            u64::from(validator_stake_info.transient_stake_lamports) == 0,
+            IdeaPadErrorCode::RebalanceInProgress
+        );
+
+        // Bump the seed so a transient account from a previous rebalance is never reused
+        let transient_stake_seed = u64::from(validator_stake_info.transient_seed_suffix)
+            .checked_add(1)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        let stake_rent = Rent::get()?.minimum_balance(StakeStateV2::size_of());
+        let minimum_delegation = spl_stake_pool::minimum_delegation(get_minimum_delegation()?);
+        let reserve_buffer = ctx.accounts.protocol_config.reserve_buffer;
+        let reserve_lamports = ctx.accounts.reserve_stake.lamports();
+
+        // The reserve has to cover its own rent and the rent of the transient account
+        let delegatable_lamports = reserve_lamports
+            .saturating_sub(stake_rent.saturating_mul(2))
+            .saturating_sub(reserve_buffer);
+        let liquid_lamports = reserve_lamports.saturating_sub(stake_rent);
+        let delegated_lamports = validator_list
+            .validators
+            .iter()
+            .fold(0u64, |total, validator| {
+                total
+                    .saturating_add(u64::from(validator.active_stake_lamports))
+                    .saturating_add(u64::from(validator.transient_stake_lamports))
+            });
+        let active_lamports = u64::from(validator_stake_info.active_stake_lamports);
+
+        if delegatable_lamports >= minimum_delegation {
+            // The reserve surplus plus everything already delegated is split by allocation weight
+            let target_lamports = ctx.accounts.validator_allocation.target_lamports(
+                &ctx.accounts.validator.key(),
+                delegated_lamports.saturating_add(delegatable_lamports),
+            )?;
+            let increase_lamports = target_lamports
+                .saturating_sub(active_lamports)
+                .min(delegatable_lamports);
+            require!(
+                increase_lamports >= minimum_delegation,
+                IdeaPadErrorCode::ValidatorAtTargetAllocation
+            );
+
+            let increase_ix = spl_stake_pool::instruction::increase_validator_stake(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.stake_pool_manager.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &ctx.accounts.validator_list.key(),
+                &ctx.accounts.reserve_stake.key(),
+                &ctx.accounts.transient_stake.key(),
+                &ctx.accounts.validator_stake.key(),
+                &ctx.accounts.validator.key(),
+                increase_lamports,
+                transient_stake_seed,
+            );
+
+            invoke_signed(
+                &increase_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts.stake_pool_manager.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    ctx.accounts.validator_list.to_account_info(),
+                    ctx.accounts.reserve_stake.to_account_info(),
+                    ctx.accounts.transient_stake.to_account_info(),
+                    ctx.accounts.validator_stake.to_account_info(),
+                    ctx.accounts.validator.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.rent.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.stake_config.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        } else if liquid_lamports < reserve_buffer {
+            // What stays delegated is split by allocation weight, so only stake above the
+            // validator's target is pulled back. The validator stake account has to stay above
+            // rent and the minimum delegation.
+            let shortfall_lamports = reserve_buffer.saturating_sub(liquid_lamports);
+            let target_lamports = ctx.accounts.validator_allocation.target_lamports(
+                &ctx.accounts.validator.key(),
+                delegated_lamports.saturating_sub(shortfall_lamports),
+            )?;
+            let decreasable_lamports = active_lamports
+                .saturating_sub(stake_rent)
+                .saturating_sub(minimum_delegation)
+                .min(active_lamports.saturating_sub(target_lamports));
+            let decrease_lamports = shortfall_lamports
+                .max(minimum_delegation)
+                .min(decreasable_lamports);
+            require!(
+                decrease_lamports >= minimum_delegation,
+                IdeaPadErrorCode::NothingToRebalance
+            );
+
+            let decrease_ix = spl_stake_pool::instruction::decrease_validator_stake_with_reserve(
+                &spl_stake_pool::id(),
+                &ctx.accounts.stake_pool.key(),
+                &ctx.accounts.stake_pool_manager.key(),
+                &ctx.accounts.stake_pool_withdrawal_authority.key(),
+                &ctx.accounts.validator_list.key(),
+                &ctx.accounts.reserve_stake.key(),
+                &ctx.accounts.validator_stake.key(),
+                &ctx.accounts.transient_stake.key(),
+                decrease_lamports,
+                transient_stake_seed,
+            );
+
+            invoke_signed(
+                &decrease_ix,
+                &[
+                    ctx.accounts.stake_pool.to_account_info(),
+                    ctx.accounts.stake_pool_manager.to_account_info(),
+                    ctx.accounts
+                        .stake_pool_withdrawal_authority
+                        .to_account_info(),
+                    ctx.accounts.validator_list.to_account_info(),
+                    ctx.accounts.reserve_stake.to_account_info(),
+                    ctx.accounts.validator_stake.to_account_info(),
+                    ctx.accounts.transient_stake.to_account_info(),
+                    ctx.accounts.clock.to_account_info(),
+                    ctx.accounts.stake_history.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.stake_program.to_account_info(),
+                    ctx.accounts.stake_pool_program.to_account_info(),
+                ],
+                signers,
+            )?;
+        } else {
+            return err!(IdeaPadErrorCode::NothingToRebalance);
+        }
+
+        Ok(())
+    }
+
+    pub fn add_project_validator(ctx: Context<AddProjectValidator>, weight: u16) -> Result<()> {
+        let project_key = ctx.accounts.project.key();
+
+        let add_validator_ix = spl_stake_pool::instruction::add_validator_to_pool(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.stake_pool_manager.key(),
+            &ctx.accounts.reserve_stake.key(),
+            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.validator_list.key(),
+            &ctx.accounts.stake_account.key(),
+            &ctx.accounts.validator.key(),
+            None,
+        );
+
+        invoke_signed(
+            &add_validator_ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_manager.to_account_info(),
+                ctx.accounts
+                    .stake_pool_withdrawal_authority
+                    .to_account_info(),
+                ctx.accounts.reserve_stake.to_account_info(),
+                ctx.accounts.validator_list.to_account_info(),
+                ctx.accounts.stake_account.to_account_info(),
+                ctx.accounts.validator.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_history.to_account_info(),
+                ctx.accounts.stake_config.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.stake_program.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            &[&[
+                b"pool_manager".as_ref(),
+                project_key.as_ref(),
+                &[ctx.accounts.stake_pool_manager.bump],
+            ]],
+        )?;
+
+        ctx.accounts
+            .validator_allocation
+            .add(ctx.accounts.validator.key(), weight)?;
+
+        Ok(())
+    }
+
+    // The validator stake is deactivated into the reserve and cleaned up by the next crank.
+    pub fn remove_project_validator(ctx: Context<RemoveProjectValidator>) -> Result<()> {
+        let project_key = ctx.accounts.project.key();
+
+        ctx.accounts
+            .validator_allocation
+            .remove(ctx.accounts.validator.key())?;
+
+        let remove_validator_ix = spl_stake_pool::instruction::remove_validator_from_pool(
+            &spl_stake_pool::id(),
+            &ctx.accounts.stake_pool.key(),
+            &ctx.accounts.stake_pool_manager.key(),
+            &ctx.accounts.stake_pool_withdrawal_authority.key(),
+            &ctx.accounts.validator_list.key(),
+            &ctx.accounts.stake_account.key(),
+            &ctx.accounts.transient_stake_account.key(),
+        );
+
+        invoke_signed(
+            &remove_validator_ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_manager.to_account_info(),
+                ctx.accounts
+                    .stake_pool_withdrawal_authority
+                    .to_account_info(),
+                ctx.accounts.validator_list.to_account_info(),
+                ctx.accounts.stake_account.to_account_info(),
+                ctx.accounts.transient_stake_account.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_program.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            &[&[
+                b"pool_manager".as_ref(),
+                project_key.as_ref(),
+                &[ctx.accounts.stake_pool_manager.bump],
+            ]],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn set_project_validator_weight(
+        ctx: Context<SetProjectValidatorWeight>,
+        vote_account: Pubkey,
+        weight: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .validator_allocation
+            .set_weight(vote_account, weight)?;
+
+        Ok(())
+    }
+
+    // Accepts a whitelisted third-party lst for the project, held in a project owned vault.
+    pub fn enable_project_lst(ctx: Context<EnableProjectLst>) -> Result<()> {
+        require!(
+            ctx.accounts.project.state == ProjectState::Draft,
+            IdeaPadErrorCode::ProjectConfigLocked
+        );
+
+        let whitelisted_lst = ctx
+            .accounts
+            .lst_registry
+            .find(&ctx.accounts.lst_mint.key())
+            .ok_or(IdeaPadErrorCode::LstNotWhitelisted)?;
+
+        ctx.accounts.lst_vault.init(
+            ctx.accounts.project.key(),
+            ctx.accounts.lst_mint.key(),
+            whitelisted_lst.stake_pool,
+            ctx.bumps.lst_vault,
+        )?;
+
+        Ok(())
+    }
+
+    // Deposits a whitelisted lst into the project vault. The lst is valued in lamports at the
+    // source pool exchange rate and that value counts as the backer principal, anything the lst
+    // appreciates above it is creator yield paid out with harvest_lst_yield.
+    pub fn deposit_lst(ctx: Context<DepositLst>, amount: u64) -> Result<()> {
+        let rate = PoolRate::load(
+            &ctx.accounts.source_stake_pool,
+            &ctx.accounts.lst_mint.key(),
+        )?;
+        let lamports = rate.to_lamports(amount)?;
+
+        let mint_receipt = &mut ctx.accounts.mint_receipt;
+        ctx.accounts
+            .project
+            .check_deposit(lamports, mint_receipt.contribution_reward.cost)?;
+
+        if !ctx.accounts.lst_deposit.is_initialized() {
+            ctx.accounts.lst_deposit.init(
+                ctx.accounts.lst_vault.key(),
+                mint_receipt.owner.key(),
+                ctx.bumps.lst_deposit,
+            )?;
+        }
+
+        let transfer_context = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::Transfer {
+                from: ctx.accounts.wallet_lst_account.to_account_info(),
+                to: ctx.accounts.lst_vault_token_account.to_account_info(),
+                authority: mint_receipt.owner.to_account_info(),
+            },
+        );
+
+        anchor_spl::token::transfer(transfer_context, amount)?;
+
+        mint_receipt.contribution_reward.record_sale()?;
+        ctx.accounts.lst_deposit.record_deposit(lamports, amount)?;
+        mint_receipt.receipt.init(
+            ctx.accounts.project.key(),
+            ctx.accounts.lst_deposit.key(),
+            mint_receipt.contribution_reward.key(),
+            mint_receipt.nft_mint.key(),
+            lamports,
+            amount,
+            ctx.bumps.mint_receipt.receipt,
+        )?;
+        ctx.accounts.lst_vault.add_principal(lamports)?;
+        ctx.accounts
+            .project
+            .record_raise(lamports, Clock::get()?.unix_timestamp)?;
+
+        mint_receipt.mint(&ctx.accounts.project)?;
+
+        Ok(())
+    }
+
+    // Pays out the lst held above backer principal, less the protocol share.
+    pub fn harvest_lst_yield(ctx: Context<HarvestLstYield>) -> Result<()> {
+        let rate = PoolRate::load(
+            &ctx.accounts.source_stake_pool,
+            &ctx.accounts.lst_mint.key(),
+        )?;
+
+        let balance = ctx.accounts.lst_vault_token_account.amount;
+        let surplus_lamports = rate
+            .to_lamports(balance)?
+            .saturating_sub(ctx.accounts.lst_vault.principal_lamports);
+        let harvested = rate.to_lst(surplus_lamports)?.min(balance);
+        require!(harvested > 0, IdeaPadErrorCode::NothingToHarvest);
+
+        let (protocol_amount, creator_amount) =
+            ctx.accounts.protocol_config.split_harvest(harvested, 0)?;
+
+        let project_key = ctx.accounts.project.key();
+        let lst_mint_key = ctx.accounts.lst_mint.key();
+        let signers: &[&[&[u8]]] = &[&[
+            b"lst_vault".as_ref(),
+            project_key.as_ref(),
+            lst_mint_key.as_ref(),
+            &[ctx.accounts.lst_vault.bump],
+        ]];
+
+        if protocol_amount > 0 {
+            let transfer_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.lst_vault_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_lst_account.to_account_info(),
+                    authority: ctx.accounts.lst_vault.to_account_info(),
+                },
+                signers,
+            );
+
+            anchor_spl::token::transfer(transfer_context, protocol_amount)?;
+        }
+
+        let transfer_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::Transfer {
+                from: ctx.accounts.lst_vault_token_account.to_account_info(),
+                to: ctx.accounts.creator_lst_account.to_account_info(),
+                authority: ctx.accounts.lst_vault.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::token::transfer(transfer_context, creator_amount)?;
+
+        ctx.accounts.lst_vault.record_harvest(harvested)?;
+
+        Ok(())
+    }
+
+    // Returns lst worth the receipt principal at the current rate once the project is redeemable,
+    // or straight away when it failed or was cancelled. Whoever holds the receipt nft is paid and
+    // the receipt is burned.
+    pub fn claim_lst(ctx: Context<ClaimLst>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            ctx.accounts.project.is_redeemable(now) || ctx.accounts.project.is_refundable(),
+            IdeaPadErrorCode::ClaimNotAvailable
+        );
+
+        let rate = PoolRate::load(
+            &ctx.accounts.source_stake_pool,
+            &ctx.accounts.lst_mint.key(),
+        )?;
+        let principal_lamports = ctx.accounts.receipt.lamports;
+        let claim_amount = rate
+            .to_lst(principal_lamports)?
+            .min(ctx.accounts.lst_vault_token_account.amount);
+
+        let project_key = ctx.accounts.project.key();
+        let lst_mint_key = ctx.accounts.lst_mint.key();
+        let signers: &[&[&[u8]]] = &[&[
+            b"lst_vault".as_ref(),
+            project_key.as_ref(),
+            lst_mint_key.as_ref(),
+            &[ctx.accounts.lst_vault.bump],
+        ]];
+
+        let transfer_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::Transfer {
+                from: ctx.accounts.lst_vault_token_account.to_account_info(),
+                to: ctx.accounts.holder_lst_account.to_account_info(),
+                authority: ctx.accounts.lst_vault.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::token::transfer(transfer_context, claim_amount)?;
+
+        burn_receipt(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.nft_token_account.to_account_info(),
+            ctx.accounts.holder.to_account_info(),
+        )?;
+
+        ctx.accounts
+            .lst_deposit
+            .record_withdrawal(principal_lamports, ctx.accounts.receipt.lst_amount)?;
+        ctx.accounts
+            .lst_vault
+            .remove_principal(principal_lamports)?;
+
+        Ok(())
+    }
+
+    pub fn initialize_protocol_config(
+        ctx: Context<InitializeProtocolConfig>,
+        treasury: Pubkey,
+        epoch_fee: FeeRate,
+        deposit_fee: FeeRate,
+        withdrawal_fee: FeeRate,
+        max_fee: FeeRate,
+        protocol_fee_share: u8,
+    ) -> Result<()> {
+        ctx.accounts.protocol_config.init(
+            ctx.accounts.admin.key(),
+            treasury,
+            epoch_fee,
+            deposit_fee,
+            withdrawal_fee,
+            max_fee,
+            protocol_fee_share,
+            ctx.bumps.protocol_config,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn update_protocol_config(
+        ctx: Context<UpdateProtocolConfig>,
+        treasury: Option<Pubkey>,
+        epoch_fee: Option<FeeRate>,
+        deposit_fee: Option<FeeRate>,
+        withdrawal_fee: Option<FeeRate>,
+        reserve_buffer: Option<u64>,
+        paused: Option<bool>,
+    ) -> Result<()> {
+        let protocol_config = &mut ctx.accounts.protocol_config;
+
+        if let Some(treasury) = treasury {
+            protocol_config.treasury = treasury;
+        }
+        if let Some(epoch_fee) = epoch_fee {
+            protocol_config.epoch_fee = epoch_fee;
+        }
+        if let Some(deposit_fee) = deposit_fee {
+            protocol_config.deposit_fee = deposit_fee;
+        }
+        if let Some(withdrawal_fee) = withdrawal_fee {
+            protocol_config.withdrawal_fee = withdrawal_fee;
+        }
+        if let Some(reserve_buffer) = reserve_buffer {
+            protocol_config.reserve_buffer = reserve_buffer;
+        }
+        if let Some(paused) = paused {
+            protocol_config.paused = paused;
+        }
+
+        protocol_config.validate_fees()?;
+
+        Ok(())
+    }
+
+    pub fn update_protocol_fee_limits(
+        ctx: Context<UpdateProtocolConfig>,
+        max_fee: Option<FeeRate>,
+        protocol_fee_share: Option<u8>,
+        crank_tip: Option<u64>,
+    ) -> Result<()> {
+        let protocol_config = &mut ctx.accounts.protocol_config;
+
+        if let Some(max_fee) = max_fee {
+            protocol_config.max_fee = max_fee;
+        }
+        if let Some(protocol_fee_share) = protocol_fee_share {
+            protocol_config.protocol_fee_share = protocol_fee_share;
+        }
+        if let Some(crank_tip) = crank_tip {
+            protocol_config.crank_tip = crank_tip;
+        }
+
+        protocol_config.validate_fees()?;
+
+        Ok(())
+    }
+
+    // Admin handoff is two step so a typo can't lock us out of the config.
+    pub fn propose_protocol_admin(
+        ctx: Context<UpdateProtocolConfig>,
+        pending_admin: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.protocol_config.pending_admin = pending_admin;
+
+        Ok(())
+    }
+
+    pub fn accept_protocol_admin(ctx: Context<AcceptProtocolAdmin>) -> Result<()> {
+        let protocol_config = &mut ctx.accounts.protocol_config;
+
+        protocol_config.admin = ctx.accounts.pending_admin.key();
+        protocol_config.pending_admin = None;
+
+        Ok(())
+    }
+
+    pub fn initialize_validator_registry(ctx: Context<InitializeValidatorRegistry>) -> Result<()> {
+        ctx.accounts
+            .validator_registry
+            .init(ctx.bumps.validator_registry)?;
+
+        Ok(())
+    }
+
+    pub fn add_registered_validator(
+        ctx: Context<ManageValidatorRegistry>,
+        vote_account: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.validator_registry.add(vote_account)?;
+
+        Ok(())
+    }
+
+    pub fn remove_registered_validator(
+        ctx: Context<ManageValidatorRegistry>,
+        vote_account: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.validator_registry.remove(vote_account)?;
+
+        Ok(())
+    }
+
+    pub fn initialize_lst_registry(ctx: Context<InitializeLstRegistry>) -> Result<()> {
+        ctx.accounts.lst_registry.init(ctx.bumps.lst_registry)?;
+
+        Ok(())
+    }
+
+    pub fn add_whitelisted_lst(
+        ctx: Context<ManageLstRegistry>,
+        mint: Pubkey,
+        stake_pool: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.lst_registry.add(mint, stake_pool)?;
+
+        Ok(())
+    }
+
+    pub fn remove_whitelisted_lst(ctx: Context<ManageLstRegistry>, mint: Pubkey) -> Result<()> {
+        ctx.accounts.lst_registry.remove(mint)?;
+
+        Ok(())
+    }
+
+
+}
+
+#[derive(Accounts)]
+#[instruction(
+    seed: Vec<u8>
+)]
+pub struct CreateProject<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"project", seed.as_slice()],
+        bump,
+        space = Project::space(&seed)
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init, 
+        payer = payer,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump,
+        space = PoolManager::space()
+    )]
+    pub stake_pool_manager: Account<'info, PoolManager>,
+
+    /// CHECK: pda used as the sol and stake deposit authority of the pool
+    #[account(
+        seeds = [b"deposit_authority", project.key().as_ref()],
+        bump
+    )]
+    pub deposit_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        // mint::authority = Pubkey::from_str("bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1").unwrap()
+    )]
+    pub pool_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    // Receives the pool fees, paid out to the creator through harvest_yield
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: initialized by the stake pool, validators are only added from validator_registry
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+
+    pub reserve_stake_account: AccountInfo<'info>,
+    pub manager_account: AccountInfo<'info>,
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub stake_account: AccountInfo<'info>,
+    #[account(
+        constraint = validator_registry.contains(&phase_validator.key()) @ IdeaPadErrorCode::ValidatorNotRegistered
+    )]
+    pub phase_validator: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"validator_registry"],
+        bump = validator_registry.bump
+    )]
+    pub validator_registry: Box<Account<'info, ValidatorRegistry>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"validator_allocation", project.key().as_ref()],
+        bump,
+        space = ValidatorAllocation::space()
+    )]
+    pub validator_allocation: Box<Account<'info, ValidatorAllocation>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.paused @ IdeaPadErrorCode::ProtocolPaused
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    pub stake_pool_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    pub stake_config: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateContributionReward<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub project: Account<'info, Project>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.paused @ IdeaPadErrorCode::ProtocolPaused
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"reward", project.key().as_ref(), &project.contribution_reward_count.to_le_bytes()],
+        bump,
+        space = ContributionReward::space()
+    )]
+    pub contribution_reward: Account<'info, ContributionReward>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = project,
+        mint::freeze_authority = project
+    )]
+    pub reward_collection_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = reward_collection_mint,
+        associated_token::authority = authority
+    )]
+    pub reward_collection_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), reward_collection_mint.key().as_ref()],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub collection_metadata: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), reward_collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub collection_master_edition: AccountInfo<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateContributionReward<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"reward", project.key().as_ref(), &contribution_reward.index.to_le_bytes()],
+        bump = contribution_reward.bump
+    )]
+    pub contribution_reward: Account<'info, ContributionReward>,
+}
+
+#[derive(Accounts)]
+pub struct GetEntitlements<'info> {
+    /// CHECK: only the key is compared against receipt owners
+    pub backer: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemReward<'info> {
+    #[account(mut)]
+    pub backer: Signer<'info>,
+
+    #[account(
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"reward", project.key().as_ref(), &contribution_reward.index.to_le_bytes()],
+        bump = contribution_reward.bump
+    )]
+    pub contribution_reward: Box<Account<'info, ContributionReward>>,
+
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        token::mint = nft_mint,
+        token::authority = backer,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = nft_mint,
+        has_one = contribution_reward,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = !receipt.redeemed @ IdeaPadErrorCode::ReceiptRedeemed
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = Metadata::id(),
+        constraint = nft_metadata.collection.as_ref().is_some_and(|collection| {
+            collection.verified && collection.key == contribution_reward.reward_collection_mint
+        }) @ IdeaPadErrorCode::ReceiptNotFromProject
+    )]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+
+    #[account(
+        init,
+        payer = backer,
+        seeds = [b"redemption", nft_mint.key().as_ref()],
+        bump,
+        space = RewardRedemption::space()
+    )]
+    pub reward_redemption: Box<Account<'info, RewardRedemption>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarkRewardFulfilled<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"reward", project.key().as_ref(), &contribution_reward.index.to_le_bytes()],
+        bump = contribution_reward.bump
+    )]
+    pub contribution_reward: Account<'info, ContributionReward>,
+
+    #[account(
+        mut,
+        has_one = contribution_reward,
+        seeds = [b"redemption", reward_redemption.receipt_mint.as_ref()],
+        bump = reward_redemption.bump
+    )]
+    pub reward_redemption: Account<'info, RewardRedemption>,
+}
+
+#[derive(Accounts)]
+pub struct PoolDeposit<'info> {
+    #[account(
+        constraint = mint_receipt.contribution_reward.project == project.key() @ IdeaPadErrorCode::RewardProjectMismatch
+    )]
+    pub mint_receipt: MintReceipt<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    // Created on the first deposit of a backer and reused for later ones
+    #[account(
+        init_if_needed,
+        payer = mint_receipt.payer,
+        seeds = [b"stake_vault", project.key().as_ref(), mint_receipt.owner.key().as_ref()],
+        bump,
+        space = StakeVault::space()
+    )]
+    pub stake_vault: Box<Account<'info, StakeVault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        init_if_needed,
+        payer = mint_receipt.payer,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_vault
+    )]
+    pub lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        address = project.stake_pool
+    )]
+    pub stake_pool: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake_account: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+
+    /// CHECK: pda set as the stake and sol deposit authority of the pool in create_project
+    #[account(
+        seeds = [b"deposit_authority", project.key().as_ref()],
+        bump
+    )]
+    pub deposit_authority: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PoolDeposit<'info> {
+    // Checks and credits a deposit the closure makes into the project pool. The stake vault of the
+    // wallet is opened on its first deposit, and the lst the pool minted to it is recorded on a new
+    // receipt.
+    fn deposit(
+        &mut self,
+        bumps: &PoolDepositBumps,
+        amount: u64,
+        invoke_deposit: impl FnOnce(&Self) -> Result<()>,
+    ) -> Result<()> {
+        self.project
+            .check_deposit(amount, self.mint_receipt.contribution_reward.cost)?;
+
+        if !self.stake_vault.is_initialized() {
+            self.stake_vault.init(
+                self.project.key(),
+                self.mint_receipt.owner.key(),
+                bumps.stake_vault,
+            )?;
+        }
+
+        let lst_before = self.lst_token_account.amount;
+
+        invoke_deposit(self)?;
+
+        self.lst_token_account.reload()?;
+        let lst_received = self
+            .lst_token_account
+            .amount
+            .checked_sub(lst_before)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        let mint_receipt = &mut self.mint_receipt;
+        mint_receipt.contribution_reward.record_sale()?;
+        self.stake_vault.record_deposit(amount, lst_received)?;
+        mint_receipt.receipt.init(
+            self.project.key(),
+            self.stake_vault.key(),
+            mint_receipt.contribution_reward.key(),
+            mint_receipt.nft_mint.key(),
+            amount,
+            lst_received,
+            bumps.mint_receipt.receipt,
+        )?;
+        self.project
+            .record_raise(amount, Clock::get()?.unix_timestamp)?;
+
+        self.mint_receipt.mint(&self.project)
+    }
+}
+
+#[derive(Accounts)]
+pub struct DepositStake<'info> {
+    pub pool_deposit: PoolDeposit<'info>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_stake_account: AccountInfo<'info>,
+
+    // Delegated stake account of the backer, authorized to the wallet
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub deposit_stake: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintReceipt<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // Receives the receipt nft
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Instructions using these accounts tie the reward to their project
+    #[account(
+        mut,
+        has_one = reward_collection_mint,
+        seeds = [b"reward", contribution_reward.project.as_ref(), &contribution_reward.index.to_le_bytes()],
+        bump = contribution_reward.bump
+    )]
+    pub contribution_reward: Box<Account<'info, ContributionReward>>,
+
+    // Authorities move to the collection master edition in create_contribution_reward, the
+    // contribution reward ties the collection to the project
+    pub reward_collection_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), reward_collection_mint.key().as_ref()],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub collection_metadata: Box<Account<'info, MetadataAccount>>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), reward_collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub collection_master_edition: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = contribution_reward,
+        mint::freeze_authority = contribution_reward
+    )]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = owner
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub nft_metadata: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub nft_master_edition: AccountInfo<'info>,
+
+    // Position carried by the receipt nft, claimable by whoever holds it
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump,
+        space = Receipt::space()
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> MintReceipt<'info> {
+    // Mints the receipt nft into the reward collection, its metadata comes from the reward
+    fn mint(&self, project: &Account<'info, Project>) -> Result<()> {
+        let project_key = project.key();
+        let reward_index = self.contribution_reward.index.to_le_bytes();
+        let reward_bump = [self.contribution_reward.bump];
+        let project_bump = [project.bump];
+
+        // The reward pda is mint authority of the receipt, the project is update authority of the
+        // receipt and its collection.
+        let signers: &[&[&[u8]]] = &[
+            &[
+                b"reward".as_ref(),
+                project_key.as_ref(),
+                &reward_index,
+                &reward_bump,
+            ],
+            &[b"project".as_ref(), project.seed.as_slice(), &project_bump],
+        ];
+
+        let mint_to_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            anchor_spl::token::MintTo {
+                mint: self.nft_mint.to_account_info(),
+                to: self.nft_token_account.to_account_info(),
+                authority: self.contribution_reward.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::token::mint_to(mint_to_context, 1)?;
+
+        let create_metadata_context = CpiContext::new_with_signer(
+            self.token_metadata.to_account_info(),
+            CreateMetadataAccountsV3 {
+                metadata: self.nft_metadata.to_account_info(),
+                mint: self.nft_mint.to_account_info(),
+                mint_authority: self.contribution_reward.to_account_info(),
+                payer: self.payer.to_account_info(),
+                update_authority: project.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            signers,
+        );
+
+        // The collection metadata holds these null padded, the reward keeps them as given
+        anchor_spl::metadata::create_metadata_accounts_v3(
+            create_metadata_context,
+            DataV2 {
+                name: self.contribution_reward.name.clone(),
+                symbol: self.contribution_reward.symbol.clone(),
+                uri: self.contribution_reward.uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            true,
+            true,
+            None,
+        )?;
+
+        let create_master_edition_context = CpiContext::new_with_signer(
+            self.token_metadata.to_account_info(),
+            CreateMasterEditionV3 {
+                metadata: self.nft_metadata.to_account_info(),
+                mint: self.nft_mint.to_account_info(),
+                mint_authority: self.contribution_reward.to_account_info(),
+                payer: self.payer.to_account_info(),
+                update_authority: project.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+                edition: self.nft_master_edition.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::metadata::create_master_edition_v3(create_master_edition_context, Some(0))?;
+
+        let set_and_verify_context = CpiContext::new_with_signer(
+            self.token_metadata.to_account_info(),
+            SetAndVerifySizedCollectionItem {
+                metadata: self.nft_metadata.to_account_info(),
+                collection_authority: project.to_account_info(),
+                payer: self.payer.to_account_info(),
+                update_authority: project.to_account_info(),
+                collection_mint: self.reward_collection_mint.to_account_info(),
+                collection_metadata: self.collection_metadata.to_account_info(),
+                collection_master_edition: self.collection_master_edition.to_account_info(),
+            },
+            signers,
+        );
+
+        anchor_spl::metadata::set_and_verify_sized_collection_item(set_and_verify_context, None)?;
+
+        Ok(())
+    }
+}
+
+// Burns a claimed receipt and closes its token account back to the holder
+fn burn_receipt<'info>(
+    token_program: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    nft_token_account: AccountInfo<'info>,
+    holder: AccountInfo<'info>,
+) -> Result<()> {
+    let burn_context = CpiContext::new(
+        token_program.clone(),
+        anchor_spl::token::Burn {
+            mint: nft_mint,
+            from: nft_token_account.clone(),
+            authority: holder.clone(),
+        },
+    );
+
+    anchor_spl::token::burn(burn_context, 1)?;
+
+    let close_nft_context = CpiContext::new(
+        token_program,
+        anchor_spl::token::CloseAccount {
+            account: nft_token_account,
+            destination: holder.clone(),
+            authority: holder,
+        },
+    );
+
+    anchor_spl::token::close_account(close_nft_context)
+}
+
+#[derive(Accounts)]
+pub struct ChangeState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveRaise<'info> {
+    #[account(
+        mut,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+}
+
+
+#[derive(Accounts)]
+pub struct ClaimBack<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"stake_vault", project.key().as_ref(), stake_vault.staker.as_ref()],
+        bump = stake_vault.bump,
+        constraint = !stake_vault.is_claimed @ IdeaPadErrorCode::AlreadyClaimed
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    // Deposit backing the receipt nft, whoever holds the nft owns it
+    #[account(
+        mut,
+        close = holder,
+        has_one = stake_vault,
+        has_one = nft_mint,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    #[account(mut)]
+    pub nft_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_vault
+    )]
+    pub lst_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = holder
+    )]
+    pub holder_lst_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        address = project.lst_mint
+    )]
+    pub pool_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBackAsSol<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"stake_vault", project.key().as_ref(), stake_vault.staker.as_ref()],
+        bump = stake_vault.bump,
+        constraint = !stake_vault.is_claimed @ IdeaPadErrorCode::AlreadyClaimed
+    )]
+    pub stake_vault: Box<Account<'info, StakeVault>>,
+
+    // Deposit backing the receipt nft, whoever holds the nft owns it
+    #[account(
+        mut,
+        close = holder,
+        has_one = stake_vault,
+        has_one = nft_mint,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_vault
+    )]
+    pub lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    // Only needed when the claim is split from pool stake
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_to_split: Option<AccountInfo<'info>>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_to_receive: Option<AccountInfo<'info>>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetEarlyExitPenalty<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Account<'info, Project>,
+}
+
+#[derive(Accounts)]
+pub struct EarlyWithdraw<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"stake_vault", project.key().as_ref(), stake_vault.staker.as_ref()],
+        bump = stake_vault.bump,
+        constraint = !stake_vault.is_claimed @ IdeaPadErrorCode::AlreadyClaimed
+    )]
+    pub stake_vault: Box<Account<'info, StakeVault>>,
+
+    // Deposit backing the receipt nft, closed once fully withdrawn. Once its reward is redeemed
+    // the deposit stays until the project is redeemable.
+    #[account(
+        mut,
+        has_one = stake_vault,
+        has_one = nft_mint,
+        has_one = contribution_reward,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = !receipt.redeemed @ IdeaPadErrorCode::ReceiptRedeemed
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Reward of the presented receipt
+    #[account(mut)]
+    pub contribution_reward: Box<Account<'info, ContributionReward>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_vault
+    )]
+    pub lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = holder
+    )]
+    pub holder_lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    // Receives the early exit penalty, paid out to the creator through harvest_yield
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDowngradedReceipt<'info> {
+    #[account(
+        constraint = mint_receipt.contribution_reward.project == project.key() @ IdeaPadErrorCode::RewardProjectMismatch
+    )]
+    pub mint_receipt: MintReceipt<'info>,
+
+    #[account(
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    // Record of the burned receipt, replaced by the one minted here and closed to the holder
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"receipt", downgraded_receipt.nft_mint.as_ref()],
+        bump = downgraded_receipt.bump
+    )]
+    pub downgraded_receipt: Box<Account<'info, Receipt>>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// CHECK: depositor the stake vault was opened for, receives its rent once emptied
+    #[account(
+        mut,
+        address = stake_vault.staker
+    )]
+    pub staker: AccountInfo<'info>,
+
+    #[account(
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"stake_vault", project.key().as_ref(), stake_vault.staker.as_ref()],
+        bump = stake_vault.bump,
+        constraint = !stake_vault.is_claimed @ IdeaPadErrorCode::AlreadyClaimed
+    )]
+    pub stake_vault: Box<Account<'info, StakeVault>>,
+
+    // Deposit backing the receipt nft, whoever holds the nft owns it
+    #[account(
+        mut,
+        close = holder,
+        has_one = stake_vault,
+        has_one = nft_mint,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_vault
+    )]
+    pub lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the refund is paid out in lst
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = holder
+    )]
+    pub holder_lst_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestYield<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: only used as the owner of treasury_lst_account
+    #[account(
+        address = protocol_config.treasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    // Created on the first harvest after the treasury is set or changed
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = pool_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_lst_account: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the harvest is paid out in lst
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = authority
+    )]
+    pub creator_lst_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankProjectPool<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = stake_pool_manager
+    )]
+    pub project_fee_account: Box<Account<'info, TokenAccount>>,
+
+    // Receives the crank tip, no tip is paid when omitted
+    #[account(
+        mut,
+        token::mint = pool_mint
+    )]
+    pub cranker_lst_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        address = project.lst_mint
+    )]
+    pub pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RebalanceProjectStake<'info> {
+    #[account(
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"validator_allocation", project.key().as_ref()],
+        bump = validator_allocation.bump
+    )]
+    pub validator_allocation: Box<Account<'info, ValidatorAllocation>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK inside instruction
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_stake: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub transient_stake: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub validator: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_config: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddProjectValidator<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"validator_allocation", project.key().as_ref()],
+        bump = validator_allocation.bump
+    )]
+    pub validator_allocation: Box<Account<'info, ValidatorAllocation>>,
+
+    #[account(
+        seeds = [b"validator_registry"],
+        bump = validator_registry.bump
+    )]
+    pub validator_registry: Box<Account<'info, ValidatorRegistry>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_account: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(
+        constraint = validator_registry.contains(&validator.key()) @ IdeaPadErrorCode::ValidatorNotRegistered
+    )]
+    pub validator: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK inside instruction
+    pub stake_config: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveProjectValidator<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        has_one = stake_pool,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        has_one = project,
+        seeds = [b"pool_manager", project.key().as_ref()],
+        bump = stake_pool_manager.bump
+    )]
+    pub stake_pool_manager: Box<Account<'info, PoolManager>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"validator_allocation", project.key().as_ref()],
+        bump = validator_allocation.bump
+    )]
+    pub validator_allocation: Box<Account<'info, ValidatorAllocation>>,
+
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+    /// CHECK inside instruction
+    pub stake_pool_withdrawal_authority: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub stake_account: AccountInfo<'info>,
+    /// CHECK inside instruction
+    #[account(mut)]
+    pub transient_stake_account: AccountInfo<'info>,
+    /// CHECK: only the vote account address is used
+    pub validator: AccountInfo<'info>,
+
+    /// CHECK inside instruction
+    #[account(
+        address = spl_stake_pool::id()
+    )]
+    pub stake_pool_program: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK inside instruction
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProjectValidatorWeight<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        seeds = [b"validator_allocation", project.key().as_ref()],
+        bump = validator_allocation.bump
+    )]
+    pub validator_allocation: Box<Account<'info, ValidatorAllocation>>,
+}
+
+#[derive(Accounts)]
+pub struct EnableProjectLst<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.paused @ IdeaPadErrorCode::ProtocolPaused
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        seeds = [b"lst_registry"],
+        bump = lst_registry.bump
+    )]
+    pub lst_registry: Box<Account<'info, LstRegistry>>,
+
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"lst_vault", project.key().as_ref(), lst_mint.key().as_ref()],
+        bump,
+        space = ProjectLstVault::space()
+    )]
+    pub lst_vault: Box<Account<'info, ProjectLstVault>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = lst_mint,
+        associated_token::authority = lst_vault
+    )]
+    pub lst_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLst<'info> {
+    #[account(
+        constraint = mint_receipt.contribution_reward.project == project.key() @ IdeaPadErrorCode::RewardProjectMismatch
+    )]
+    pub mint_receipt: MintReceipt<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        has_one = lst_mint,
+        has_one = source_stake_pool,
+        seeds = [b"lst_vault", project.key().as_ref(), lst_mint.key().as_ref()],
+        bump = lst_vault.bump
+    )]
+    pub lst_vault: Box<Account<'info, ProjectLstVault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = lst_vault
+    )]
+    pub lst_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = lst_mint,
+        token::authority = mint_receipt.owner
+    )]
+    pub wallet_lst_account: Box<Account<'info, TokenAccount>>,
+
+    // Created on the first deposit of a backer in this lst and reused for later ones
+    #[account(
+        init_if_needed,
+        payer = mint_receipt.payer,
+        seeds = [b"lst_deposit", lst_vault.key().as_ref(), mint_receipt.owner.key().as_ref()],
+        bump,
+        space = LstDeposit::space()
+    )]
+    pub lst_deposit: Box<Account<'info, LstDeposit>>,
+
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: exchange rate is read inside instruction
+    pub source_stake_pool: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestLstYield<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
     #[account(
         has_one = authority,
         seeds = [b"project", project.seed.as_slice()],
         bump = project.bump
     )]
-    pub project: Account<'info, Project>,
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        has_one = lst_mint,
+        has_one = source_stake_pool,
+        seeds = [b"lst_vault", project.key().as_ref(), lst_mint.key().as_ref()],
+        bump = lst_vault.bump
+    )]
+    pub lst_vault: Box<Account<'info, ProjectLstVault>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = lst_vault
+    )]
+    pub lst_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: only used as the owner of treasury_lst_account
+    #[account(
+        address = protocol_config.treasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    // Created on the first harvest after the treasury is set or changed
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = lst_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_lst_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = lst_mint,
+        token::authority = authority
+    )]
+    pub creator_lst_account: Box<Account<'info, TokenAccount>>,
+
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: exchange rate is read inside instruction
+    pub source_stake_pool: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLst<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"project", project.seed.as_slice()],
+        bump = project.bump
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        has_one = project,
+        has_one = lst_mint,
+        has_one = source_stake_pool,
+        seeds = [b"lst_vault", project.key().as_ref(), lst_mint.key().as_ref()],
+        bump = lst_vault.bump
+    )]
+    pub lst_vault: Box<Account<'info, ProjectLstVault>>,
+
+    #[account(
+        mut,
+        has_one = lst_vault,
+        seeds = [b"lst_deposit", lst_vault.key().as_ref(), lst_deposit.staker.as_ref()],
+        bump = lst_deposit.bump
+    )]
+    pub lst_deposit: Box<Account<'info, LstDeposit>>,
+
+    // Deposit backing the receipt nft, whoever holds the nft owns it
+    #[account(
+        mut,
+        close = holder,
+        has_one = project,
+        has_one = nft_mint,
+        seeds = [b"receipt", nft_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.stake_vault == lst_deposit.key() @ IdeaPadErrorCode::ReceiptPositionMismatch
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        constraint = nft_token_account.amount == 1 @ IdeaPadErrorCode::ReceiptNotHeld
+    )]
+    pub nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = lst_vault
+    )]
+    pub lst_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = lst_mint,
+        token::authority = holder
+    )]
+    pub holder_lst_account: Box<Account<'info, TokenAccount>>,
+
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: exchange rate is read inside instruction
+    pub source_stake_pool: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // Only the upgrade authority can claim the admin role, so deployment cannot be front-run
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+    )]
+    pub program: Program<'info, crate::program::IdeapadPrograms>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ IdeaPadErrorCode::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"protocol_config"],
+        bump,
+        space = ProtocolConfig::space()
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptProtocolAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.pending_admin == Some(pending_admin.key()) @ IdeaPadErrorCode::InvalidPendingAdmin
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeValidatorRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"validator_registry"],
+        bump,
+        space = ValidatorRegistry::space()
+    )]
+    pub validator_registry: Account<'info, ValidatorRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageValidatorRegistry<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"validator_registry"],
+        bump = validator_registry.bump
+    )]
+    pub validator_registry: Account<'info, ValidatorRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLstRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"lst_registry"],
+        bump,
+        space = LstRegistry::space()
+    )]
+    pub lst_registry: Account<'info, LstRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageLstRegistry<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"lst_registry"],
+        bump = lst_registry.bump
+    )]
+    pub lst_registry: Account<'info, LstRegistry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ProjectState {
+    Draft,
+    Raising,
+    Funded,
+    Complete,
+    Failed,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ProjectConfig {
+    redeemption_stamp: Option<i64>,
+    min_stake_amount: u64,
+    // Lamports the project needs to raise to be Funded
+    goal: u64,
+    // Deposits close and the raise can be resolved after this timestamp
+    raise_end_stamp: i64,
+    // Lamports deposited through contribution rewards
+    total_raised: u64,
+    // Share of lst kept by the project on early_withdraw, zero when unset
+    early_exit_penalty: FeeRate,
+}
+
+#[account]
+pub struct Project {
+    pub state: ProjectState,
+    pub authority: Pubkey,
+    pub config: ProjectConfig,
+    pub raising_at: Option<i64>,
+    pub stake_pool: Pubkey,
+    pub lst_mint: Pubkey,
+    // Count for contribution_reward account pda generation
+    pub contribution_reward_count: u16,
+    // Cumulative fee lst paid out through harvest_yield, including the protocol share
+    pub harvested_yield: u64,
+    // Epoch of the last crank_project_pool run
+    pub last_update_epoch: u64,
+    // Fee lst tipped to crankers since the last harvest, taken out of the protocol share
+    pub crank_tips: u64,
+    pub seed: Vec<u8>,
+    pub bump: u8,
+}
+/*
+ * At the moment we use a random vec<u8> as identifier/seed for project, but this could be a parent collection nft
+ * that each reward references creating a tree of association to the project and validating the tie to the project for a
+ * reward/contrinution nft. ??
+ */
+
+impl Project {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        authority: Pubkey,
+        redeemption_stamp: Option<i64>,
+        min_stake_amount: u64,
+        goal: u64,
+        raise_end_stamp: i64,
+        stake_pool: Pubkey,
+        lst_mint: Pubkey,
+        seed: Vec<u8>,
+        bump: u8,
+    ) -> Result<()> {
+        let config: ProjectConfig = ProjectConfig {
+            redeemption_stamp,
+            min_stake_amount,
+            goal,
+            raise_end_stamp,
+            total_raised: 0,
+            early_exit_penalty: FeeRate::default(),
+        };
+        self.authority = authority;
+        self.config = config;
+        self.state = ProjectState::Draft;
+        self.bump = bump;
+        self.raising_at = None;
+        self.contribution_reward_count = 0;
+        self.harvested_yield = 0;
+        self.last_update_epoch = 0;
+        self.crank_tips = 0;
+        self.seed = seed;
+        self.stake_pool = stake_pool;
+        self.lst_mint = lst_mint;
+        Ok(())
+    }
+
+    pub fn space(seed: &[u8]) -> usize {
+        8 + 1
+            + 32
+            + mem::size_of::<ProjectConfig>()
+            + mem::size_of::<Option<i64>>()
+            + 32
+            + 32
+            + mem::size_of::<u16>()
+            + mem::size_of::<u64>()
+            + mem::size_of::<u64>()
+            + mem::size_of::<u64>()
+            + 4
+            + seed.len()
+            + 1
+    }
+
+    /**
+     * Draft -> Raising -> Funded/Failed -> Complete, with Cancelled reachable before funding.
+     * Failed, Cancelled and Complete are terminal.
+     */
+    pub fn transition(&mut self, state: ProjectState, now: i64) -> Result<()> {
+        match (self.state, state) {
+            (ProjectState::Draft, ProjectState::Raising) => {
+                require!(
+                    self.contribution_reward_count > 0,
+                    IdeaPadErrorCode::NoContributionRewards
+                );
+                require!(
+                    now < self.config.raise_end_stamp,
+                    IdeaPadErrorCode::RaiseEnded
+                );
+                self.raising_at = Some(now);
+            }
+            (ProjectState::Draft, ProjectState::Cancelled) => {}
+            (ProjectState::Draft, _) => return err!(IdeaPadErrorCode::InvalidTransitionFromDraft),
+            (ProjectState::Raising, ProjectState::Funded)
+            | (ProjectState::Raising, ProjectState::Failed)
+            | (ProjectState::Raising, ProjectState::Cancelled) => {}
+            (ProjectState::Raising, _) => {
+                return err!(IdeaPadErrorCode::InvalidTransitionFromRaising)
+            }
+            (ProjectState::Funded, ProjectState::Complete) => {
+                if let Some(redeemption_stamp) = self.config.redeemption_stamp {
+                    require!(
+                        now >= redeemption_stamp,
+                        IdeaPadErrorCode::RedeemptionStampNotReached
+                    );
+                }
+            }
+            (ProjectState::Funded, _) => {
+                return err!(IdeaPadErrorCode::InvalidTransitionFromFunded)
+            }
+            (ProjectState::Complete, _)
+            | (ProjectState::Failed, _)
+            | (ProjectState::Cancelled, _) => return err!(IdeaPadErrorCode::ProjectFinalized),
+        }
+
+        self.state = state;
+        Ok(())
+    }
+
+    // Backers can take their stake back once the project is Complete, or Funded with the redeemption stamp passed.
+    pub fn is_redeemable(&self, now: i64) -> bool {
+        match self.state {
+            ProjectState::Complete => true,
+            ProjectState::Funded => self
+                .config
+                .redeemption_stamp
+                .is_some_and(|redeemption_stamp| now >= redeemption_stamp),
+            _ => false,
+        }
+    }
+
+    pub fn check_deposit(&self, amount: u64, reward_cost: u64) -> Result<()> {
+        require!(
+            self.state == ProjectState::Raising,
+            IdeaPadErrorCode::ProjectNotRaising
+        );
+        require!(
+            amount >= self.config.min_stake_amount,
+            IdeaPadErrorCode::BelowMinStakeAmount
+        );
+        require!(amount >= reward_cost, IdeaPadErrorCode::BelowRewardCost);
+
+        Ok(())
+    }
+
+    pub fn early_exit_penalty(&self) -> FeeRate {
+        self.config.early_exit_penalty
+    }
+
+    pub fn set_early_exit_penalty(&mut self, penalty: FeeRate) -> Result<()> {
+        self.config.early_exit_penalty = penalty.validate()?;
+        Ok(())
+    }
+
+    // Withdrawals while raising count against the goal
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        if self.state == ProjectState::Raising {
+            self.config.total_raised = self.config.total_raised.saturating_sub(amount);
+        }
+
+        Ok(())
+    }
+
+    pub fn record_raise(&mut self, amount: u64, now: i64) -> Result<()> {
+        require!(
+            now < self.config.raise_end_stamp,
+            IdeaPadErrorCode::RaiseEnded
+        );
+
+        self.config.total_raised = self
+            .config
+            .total_raised
+            .checked_add(amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
+    pub fn resolve_raise(&mut self, now: i64) -> Result<()> {
+        require!(
+            now >= self.config.raise_end_stamp,
+            IdeaPadErrorCode::RaiseNotEnded
+        );
+
+        if self.config.total_raised >= self.config.goal {
+            self.transition(ProjectState::Funded, now)
+        } else {
+            self.transition(ProjectState::Failed, now)
+        }
+    }
+
+    // Backers can be refunded once the raise failed or the creator cancelled.
+    pub fn is_refundable(&self) -> bool {
+        self.state == ProjectState::Failed || self.state == ProjectState::Cancelled
+    }
+
+    pub fn record_harvest(&mut self, amount: u64) -> Result<()> {
+        self.harvested_yield = self
+            .harvested_yield
+            .checked_add(amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.crank_tips = 0;
+
+        Ok(())
+    }
+
+    // Only pools that are raising or holding backer stake are worth paying to keep updated
+    pub fn pays_crank_tip(&self) -> bool {
+        matches!(self.state, ProjectState::Raising | ProjectState::Funded)
+            && self.config.total_raised > 0
+    }
+
+    pub fn record_crank_tip(&mut self, amount: u64) -> Result<()> {
+        self.crank_tips = self
+            .crank_tips
+            .checked_add(amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn increment_contribution_reward_count(&mut self) -> Result<u16> {
+        self.contribution_reward_count = self
+            .contribution_reward_count
+            .checked_add(1)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(self.contribution_reward_count)
+    }
+}
+
+// Nice in theory probably has to be fleshed out a bit more
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RewardType {
+    Additive,  // Include all rewards up until this one that is additive
+    Exclusive, // Only include this reward
+}
+
+#[account]
+pub struct ContributionReward {
+    pub reward_type: RewardType,
+    pub project: Pubkey,
+    // Position in project.contribution_reward_count used for pda seeds
+    pub index: u16,
+    // Nft used at the end to gain access to reward
+    pub reward_collection_mint: Pubkey,
+    pub cost: u64,
+    pub quantity: Option<u32>, // if None it is unlimited
+    pub sold: u32,
+    // Metadata of the reward collection, copied onto receipts
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub bump: u8,
+}
+
+impl ContributionReward {
+    // Metaplex metadata limits
+    pub const MAX_NAME_LENGTH: usize = 32;
+    pub const MAX_SYMBOL_LENGTH: usize = 10;
+    pub const MAX_URI_LENGTH: usize = 200;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        reward_type: RewardType,
+        project: Pubkey,
+        index: u16,
+        reward_collection_mint: Pubkey,
+        cost: u64,
+        quantity: Option<u32>,
+        name: String,
+        symbol: String,
+        uri: String,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            name.len() <= Self::MAX_NAME_LENGTH
+                && symbol.len() <= Self::MAX_SYMBOL_LENGTH
+                && uri.len() <= Self::MAX_URI_LENGTH,
+            IdeaPadErrorCode::RewardMetadataTooLong
+        );
+
+        self.reward_type = reward_type;
+        self.project = project;
+        self.index = index;
+        self.reward_collection_mint = reward_collection_mint;
+        self.cost = cost;
+        self.quantity = quantity;
+        self.sold = 0;
+        self.name = name;
+        self.symbol = symbol;
+        self.uri = uri;
+        self.bump = bump;
+
+        Ok(())
+    }
+
+    pub fn record_sale(&mut self) -> Result<()> {
+        if let Some(quantity) = self.quantity {
+            require!(self.sold < quantity, IdeaPadErrorCode::RewardSoldOut);
+        }
+
+        self.sold = self
+            .sold
+            .checked_add(1)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn release_sale(&mut self) -> Result<()> {
+        self.sold = self
+            .sold
+            .checked_sub(1)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn set_quantity(&mut self, quantity: Option<u32>) -> Result<()> {
+        if let Some(quantity) = quantity {
+            require!(quantity >= self.sold, IdeaPadErrorCode::QuantityBelowSold);
+        }
+
+        self.quantity = quantity;
+
+        Ok(())
+    }
+
+    // None is unlimited, so it raises any cap and no cap lowers it
+    pub fn raises_quantity(&self, quantity: Option<u32>) -> bool {
+        match (self.quantity, quantity) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(current), Some(quantity)) => quantity >= current,
+        }
+    }
+
+    // Deserializes the rewards of a project passed in index order
+    pub fn load_all<'info>(
+        project: &Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Self>> {
+        let mut rewards = Vec::with_capacity(accounts.len());
+        for (index, reward_account) in accounts.iter().enumerate() {
+            let reward = Account::<ContributionReward>::try_from(reward_account)?;
+            require!(
+                reward.project == *project && reward.index as usize == index,
+                IdeaPadErrorCode::InvalidRewardAccounts
+            );
+            rewards.push(reward.into_inner());
+        }
+
+        Ok(rewards)
+    }
+
+    // Most expensive reward still available that lamports covers
+    pub fn highest_affordable(rewards: &[ContributionReward], lamports: u64) -> Option<u16> {
+        rewards
+            .iter()
+            .filter(|reward| reward.cost <= lamports)
+            .filter(|reward| {
+                reward
+                    .quantity
+                    .map_or(true, |quantity| reward.sold < quantity)
+            })
+            .max_by_key(|reward| reward.cost)
+            .map(|reward| reward.index)
+    }
+
+    /**
+     * Tiers are ordered by index. A held Additive reward grants every Additive reward up to and
+     * including it, a held Exclusive reward grants only itself.
+     */
+    pub fn entitlements(rewards: &[ContributionReward], held: &[u16]) -> Vec<u16> {
+        let mut entitled = vec![false; rewards.len()];
+
+        for &index in held {
+            let Some(reward) = rewards.get(index as usize) else {
+                continue;
+            };
+
+            match reward.reward_type {
+                RewardType::Additive => rewards
+                    .iter()
+                    .take(index as usize + 1)
+                    .filter(|reward| reward.reward_type == RewardType::Additive)
+                    .for_each(|reward| entitled[reward.index as usize] = true),
+                RewardType::Exclusive => entitled[index as usize] = true,
+            }
+        }
+
+        rewards
+            .iter()
+            .filter(|reward| entitled[reward.index as usize])
+            .map(|reward| reward.index)
+            .collect()
+    }
+
+    pub fn space() -> usize {
+        8 + 1
+            + 32
+            + mem::size_of::<u16>()
+            + 32
+            + mem::size_of::<u64>()
+            + mem::size_of::<Option<u32>>()
+            + mem::size_of::<u32>()
+            + 4
+            + Self::MAX_NAME_LENGTH
+            + 4
+            + Self::MAX_SYMBOL_LENGTH
+            + 4
+            + Self::MAX_URI_LENGTH
+            + 1
+    }
+}
+
+// Audit trail of a receipt redeemed for its reward and the creator delivering it
+#[account]
+pub struct RewardRedemption {
+    pub contribution_reward: Pubkey,
+    pub backer: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub redeemed_at: i64,
+    pub fulfilled_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl RewardRedemption {
+    pub fn init(
+        &mut self,
+        contribution_reward: Pubkey,
+        backer: Pubkey,
+        receipt_mint: Pubkey,
+        redeemed_at: i64,
+        bump: u8,
+    ) -> Result<()> {
+        self.contribution_reward = contribution_reward;
+        self.backer = backer;
+        self.receipt_mint = receipt_mint;
+        self.redeemed_at = redeemed_at;
+        self.fulfilled_at = None;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn fulfill(&mut self, now: i64) -> Result<()> {
+        require!(
+            self.fulfilled_at.is_none(),
+            IdeaPadErrorCode::RewardAlreadyFulfilled
+        );
+
+        self.fulfilled_at = Some(now);
+        Ok(())
+    }
+
+    pub fn space() -> usize {
+        8 + 32 + 32 + 32 + mem::size_of::<i64>() + mem::size_of::<Option<i64>>() + 1
+    }
+}
+
+#[account]
+pub struct StakeVault {
+    pub project: Pubkey,
+    pub staker: Pubkey,
+    pub is_claimed: bool,
+    // Cumulative lamports deposited by the staker
+    pub deposited_lamports: u64,
+    // Cumulative lst minted to the vault for those deposits
+    pub lst_received: u64,
+    pub rewards_purchased: u32,
+    pub bump: u8,
+}
+
+impl StakeVault {
+    pub fn init(&mut self, project: Pubkey, staker: Pubkey, bump: u8) -> Result<()> {
+        self.project = project;
+        self.staker = staker;
+        self.is_claimed = false;
+        self.deposited_lamports = 0;
+        self.lst_received = 0;
+        self.rewards_purchased = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // init_if_needed hands back a zeroed account on the first deposit
+    pub fn is_initialized(&self) -> bool {
+        self.staker != Pubkey::default()
+    }
+
+    // Removes a withdrawn or claimed receipt share from the vault totals
+    pub fn record_withdrawal(&mut self, lamports: u64, lst: u64) -> Result<()> {
+        self.deposited_lamports = self
+            .deposited_lamports
+            .checked_sub(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.lst_received = self
+            .lst_received
+            .checked_sub(lst)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn record_deposit(&mut self, lamports: u64, lst: u64) -> Result<()> {
+        self.deposited_lamports = self
+            .deposited_lamports
+            .checked_add(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.lst_received = self
+            .lst_received
+            .checked_add(lst)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.rewards_purchased = self
+            .rewards_purchased
+            .checked_add(1)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn space() -> usize {
+        8 + 32
+            + 32
+            + mem::size_of::<bool>()
+            + mem::size_of::<u64>()
+            + mem::size_of::<u64>()
+            + mem::size_of::<u32>()
+            + 1
+    }
+}
+
+// Tier a downgraded position can be reminted at and who may claim it
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PendingReceipt {
+    pub reward_index: u16,
+    pub holder: Pubkey,
+}
+
+// Deposit carried by a receipt nft. The lst stays in the stake vault of the depositor and is paid
+// out to whoever holds the receipt.
+#[account]
+pub struct Receipt {
+    pub project: Pubkey,
+    // Stake vault, or lst deposit for receipts minted by deposit_lst
+    pub stake_vault: Pubkey,
+    pub contribution_reward: Pubkey,
+    pub nft_mint: Pubkey,
+    pub lamports: u64,
+    // Lst minted to the stake vault, or deposited into the lst vault, for lamports
+    pub lst_amount: u64,
+    // Set once early_withdraw burns the receipt below its tier
+    pub pending: Option<PendingReceipt>,
+    // Set by redeem_reward and carried over to a downgraded receipt
+    pub redeemed: bool,
+    pub bump: u8,
+}
+
+impl Receipt {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        project: Pubkey,
+        stake_vault: Pubkey,
+        contribution_reward: Pubkey,
+        nft_mint: Pubkey,
+        lamports: u64,
+        lst_amount: u64,
+        bump: u8,
+    ) -> Result<()> {
+        self.project = project;
+        self.stake_vault = stake_vault;
+        self.contribution_reward = contribution_reward;
+        self.nft_mint = nft_mint;
+        self.lamports = lamports;
+        self.lst_amount = lst_amount;
+        self.pending = None;
+        self.redeemed = false;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // Removes the share of the deposit backing lst_amount, returning its lamports
+    pub fn record_withdrawal(&mut self, lst_amount: u64) -> Result<u64> {
+        let lamports = (self.lamports as u128)
+            .checked_mul(lst_amount as u128)
+            .and_then(|amount| amount.checked_div(self.lst_amount as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        self.lamports = self
+            .lamports
+            .checked_sub(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.lst_amount = self
+            .lst_amount
+            .checked_sub(lst_amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(lamports)
+    }
+
+    pub fn space() -> usize {
+        // pending is an option tag, a u16 index and the holder key
+        8 + 32 + 32 + 32 + 32 + mem::size_of::<u64>() + mem::size_of::<u64>() + 1 + 2 + 32 + 1 + 1
+    }
+}
+
+#[account]
+pub struct PoolManager {
+    pub project: Pubkey,
+    pub bump: u8,
+}
+
+impl PoolManager {
+    pub fn init(&mut self, project: Pubkey, bump: u8) -> Result<()> {
+        self.project = project;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn space() -> usize {
+        8 + 32 + 1
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct FeeRate {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl FeeRate {
+    // 0/0 is no fee. The stake pool rejects any other fee above one, including n/0.
+    pub fn validate(self) -> Result<Self> {
+        require!(
+            self.numerator <= self.denominator,
+            IdeaPadErrorCode::FeeTooHigh
+        );
+        Ok(self)
+    }
+
+    pub fn apply(&self, amount: u64) -> Result<u64> {
+        if self.denominator == 0 {
+            return Ok(0);
+        }
+
+        Ok((amount as u128)
+            .checked_mul(self.numerator as u128)
+            .and_then(|amount| amount.checked_div(self.denominator as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?)
+    }
+
+    pub fn exceeds(&self, max: &FeeRate) -> bool {
+        if self.denominator == 0 || self.numerator == 0 {
+            return false;
+        }
+        if max.denominator == 0 {
+            return true;
+        }
+
+        (self.numerator as u128) * (max.denominator as u128)
+            > (max.numerator as u128) * (self.denominator as u128)
+    }
+}
+
+impl From<FeeRate> for spl_stake_pool::state::Fee {
+    fn from(fee: FeeRate) -> Self {
+        spl_stake_pool::state::Fee {
+            denominator: fee.denominator,
+            numerator: fee.numerator,
+        }
+    }
+}
+
+// Singleton holding the admin and the defaults new project stake pools are created with
+#[account]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub treasury: Pubkey,
+    pub epoch_fee: FeeRate,
+    pub deposit_fee: FeeRate,
+    pub withdrawal_fee: FeeRate,
+    // Upper bound for any fee a project sets on its pool
+    pub max_fee: FeeRate,
+    // Percent of the fees harvested from project pools and lst vaults that goes to the treasury.
+    // Pools carry no referral fee, so this is the only protocol cut of deposit fees.
+    pub protocol_fee_share: u8,
+    // Lamports worth of pool lst tipped to the first crank_project_pool caller each epoch
+    pub crank_tip: u64,
+    // Lamports rebalance_project_stake leaves undelegated in each pool reserve for withdrawals
+    pub reserve_buffer: u64,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl ProtocolConfig {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        admin: Pubkey,
+        treasury: Pubkey,
+        epoch_fee: FeeRate,
+        deposit_fee: FeeRate,
+        withdrawal_fee: FeeRate,
+        max_fee: FeeRate,
+        protocol_fee_share: u8,
+        bump: u8,
+    ) -> Result<()> {
+        self.admin = admin;
+        self.pending_admin = None;
+        self.treasury = treasury;
+        self.epoch_fee = epoch_fee;
+        self.deposit_fee = deposit_fee;
+        self.withdrawal_fee = withdrawal_fee;
+        self.max_fee = max_fee;
+        self.protocol_fee_share = protocol_fee_share;
+        self.crank_tip = 0;
+        self.reserve_buffer = 0;
+        self.paused = false;
+        self.bump = bump;
+        self.validate_fees()
+    }
+
+    pub fn validate_fees(&self) -> Result<()> {
+        require!(
+            self.protocol_fee_share <= 100,
+            IdeaPadErrorCode::InvalidProtocolFeeShare
+        );
+
+        self.max_fee.validate()?;
+        self.project_fee(self.epoch_fee)?;
+        self.project_fee(self.deposit_fee)?;
+        self.project_fee(self.withdrawal_fee)?;
+        Ok(())
+    }
+
+    pub fn project_fee(&self, fee: FeeRate) -> Result<FeeRate> {
+        let fee = fee.validate()?;
+        require!(
+            !fee.exceeds(&self.max_fee),
+            IdeaPadErrorCode::FeeAboveProtocolMax
+        );
+        Ok(fee)
+    }
+
+    // Splits harvested lst into the protocol and creator amounts, rounding in the creator's favour.
+    // Crank tips already paid from the fees count towards the protocol amount.
+    pub fn split_harvest(&self, harvested: u64, crank_tips: u64) -> Result<(u64, u64)> {
+        let protocol_amount = (harvested as u128)
+            .checked_add(crank_tips as u128)
+            .and_then(|amount| amount.checked_mul(self.protocol_fee_share as u128))
+            .and_then(|amount| amount.checked_div(100))
+            .map(|amount| amount.saturating_sub(crank_tips as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        let creator_amount = harvested
+            .checked_sub(protocol_amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok((protocol_amount, creator_amount))
+    }
+
+    // The crank tip in pool lst, capped by what is left of the protocol share of the fees
+    pub fn crank_tip_lst(&self, rate: &PoolRate, fee_balance: u64, crank_tips: u64) -> Result<u64> {
+        if self.crank_tip == 0 || rate.total_lamports == 0 {
+            return Ok(0);
+        }
+
+        let (protocol_amount, _) = self.split_harvest(fee_balance, crank_tips)?;
+        Ok(rate.to_lst(self.crank_tip)?.min(protocol_amount))
+    }
+
+    pub fn space() -> usize {
+        8 + 32 + 1 + 32 + 32 + 4 * mem::size_of::<FeeRate>() + 1 + 8 + 8 + 1 + 1
+    }
+}
+
+// Vote accounts that project stake pools are allowed to delegate to
+#[account]
+pub struct ValidatorRegistry {
+    pub validators: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl ValidatorRegistry {
+    pub const MAX_VALIDATORS: usize = 64;
+
+    pub fn init(&mut self, bump: u8) -> Result<()> {
+        self.validators = Vec::new();
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn contains(&self, vote_account: &Pubkey) -> bool {
+        self.validators.contains(vote_account)
+    }
+
+    pub fn add(&mut self, vote_account: Pubkey) -> Result<()> {
+        require!(
+            !self.contains(&vote_account),
+            IdeaPadErrorCode::ValidatorAlreadyRegistered
+        );
+        require!(
+            self.validators.len() < Self::MAX_VALIDATORS,
+            IdeaPadErrorCode::ValidatorRegistryFull
+        );
+
+        self.validators.push(vote_account);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, vote_account: Pubkey) -> Result<()> {
+        let position = self
+            .validators
+            .iter()
+            .position(|validator| *validator == vote_account)
+            .ok_or(IdeaPadErrorCode::ValidatorNotRegistered)?;
+
+        self.validators.swap_remove(position);
+        Ok(())
+    }
+
+    pub fn space() -> usize {
+        8 + 4 + 32 * Self::MAX_VALIDATORS + 1
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
-pub enum ProjectState {
-    Draft,
-    Raising,
-    Funded,
-    Complete,
+pub struct ValidatorWeight {
+    pub vote_account: Pubkey,
+    pub weight: u16,
+}
+
+// Target split of a project pool's stake across its validators
+#[account]
+pub struct ValidatorAllocation {
+    pub project: Pubkey,
+    pub validators: Vec<ValidatorWeight>,
+    pub bump: u8,
+}
+
+impl ValidatorAllocation {
+    pub const MAX_VALIDATORS: usize = 10;
+    pub const DEFAULT_WEIGHT: u16 = 100;
+
+    pub fn init(&mut self, project: Pubkey, bump: u8) -> Result<()> {
+        self.project = project;
+        self.validators = Vec::new();
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn contains(&self, vote_account: &Pubkey) -> bool {
+        self.validators
+            .iter()
+            .any(|validator| validator.vote_account == *vote_account)
+    }
+
+    pub fn add(&mut self, vote_account: Pubkey, weight: u16) -> Result<()> {
+        require!(
+            !self.contains(&vote_account),
+            IdeaPadErrorCode::ValidatorAlreadyInPool
+        );
+        require!(
+            self.validators.len() < Self::MAX_VALIDATORS,
+            IdeaPadErrorCode::ProjectValidatorsFull
+        );
+
+        self.validators.push(ValidatorWeight {
+            vote_account,
+            weight,
+        });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, vote_account: Pubkey) -> Result<()> {
+        require!(
+            self.validators.len() > 1,
+            IdeaPadErrorCode::LastProjectValidator
+        );
+
+        let position = self
+            .validators
+            .iter()
+            .position(|validator| validator.vote_account == vote_account)
+            .ok_or(IdeaPadErrorCode::ValidatorNotInPool)?;
+
+        self.validators.swap_remove(position);
+        Ok(())
+    }
+
+    pub fn set_weight(&mut self, vote_account: Pubkey, weight: u16) -> Result<()> {
+        let validator = self
+            .validators
+            .iter_mut()
+            .find(|validator| validator.vote_account == vote_account)
+            .ok_or(IdeaPadErrorCode::ValidatorNotInPool)?;
+
+        validator.weight = weight;
+        Ok(())
+    }
+
+    // Share of total_lamports the validator should hold given its weight
+    pub fn target_lamports(&self, vote_account: &Pubkey, total_lamports: u64) -> Result<u64> {
+        let weight = self
+            .validators
+            .iter()
+            .find(|validator| validator.vote_account == *vote_account)
+            .ok_or(IdeaPadErrorCode::ValidatorNotInPool)?
+            .weight;
+        let total_weight: u64 = self
+            .validators
+            .iter()
+            .map(|validator| validator.weight as u64)
+            .sum();
+
+        if total_weight == 0 {
+            return Ok(0);
+        }
+
+        Ok(((total_lamports as u128) * (weight as u128) / (total_weight as u128)) as u64)
+    }
+
+    pub fn space() -> usize {
+        8 + 32 + 4 + Self::MAX_VALIDATORS * (32 + mem::size_of::<u16>()) + 1
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
-pub struct ProjectConfig {
-    redeemption_stamp: Option<i64>,
-    min_stake_amount: u64,
+pub struct WhitelistedLst {
+    pub mint: Pubkey,
+    // Spl stake pool the lst is valued against
+    pub stake_pool: Pubkey,
 }
 
+// Third-party lst projects can accept instead of fresh sol. Only spl stake pool lsts are valued,
+// marinade msol keeps its rate in its own state layout and cannot be whitelisted.
 #[account]
-pub struct Project {
-    pub state: ProjectState,
-    pub authority: Pubkey,
-    pub config: ProjectConfig,
-    pub raising_at: Option<i64>,
-    pub stake_pool: Pubkey,
-    pub lst_mint: Pubkey,
-    // Count for contribution_reward account pda generation
-    pub contribution_reward_count: u8,
-    pub seed: Vec<u8>,
+pub struct LstRegistry {
+    pub lsts: Vec<WhitelistedLst>,
     pub bump: u8,
 }
-/**
- * At the moment we use a random vec<u8> as identifier/seed for project, but this could be a parent collection nft
- * that each reward references creating a tree of association to the project and validating the tie to the project for a
- * reward/contrinution nft. ??
- */
 
-impl Project {
-    pub fn init(
-        &mut self,
-        authority: Pubkey,
-        redeemption_stamp: Option<i64>,
-        min_stake_amount: u64,
-        stake_pool: Pubkey,
-        lst_mint: Pubkey,
-        seed: Vec<u8>,
-        bump: u8,
-    ) -> Result<()> {
-        let config: ProjectConfig = ProjectConfig {
-            redeemption_stamp,
-            min_stake_amount,
-        };
-        self.authority = authority;
-        self.config = config;
-        self.state = ProjectState::Draft;
+impl LstRegistry {
+    pub const MAX_LSTS: usize = 16;
+
+    pub fn init(&mut self, bump: u8) -> Result<()> {
+        self.lsts = Vec::new();
         self.bump = bump;
-        self.raising_at = None;
-        self.contribution_reward_count = 0;
-        self.seed = seed;
-        self.stake_pool = stake_pool;
-        self.lst_mint = lst_mint;
         Ok(())
     }
 
-    pub fn space(seed: &Vec<u8>) -> usize {
-        8 + 1 + 32 + 32 + 32 + mem::size_of::<ProjectConfig>() + 1 + 1 + 4 + seed.len()
+    pub fn find(&self, mint: &Pubkey) -> Option<&WhitelistedLst> {
+        self.lsts.iter().find(|lst| lst.mint == *mint)
     }
 
-    pub fn increment_contribution_reward_count(&mut self) -> Result<u8> {
-        self.contribution_reward_count
-            .checked_add(1)
-            .ok_or(IdeaPadErrorCode::NumericalOverflow.into())
+    pub fn add(&mut self, mint: Pubkey, stake_pool: Pubkey) -> Result<()> {
+        require!(
+            self.find(&mint).is_none(),
+            IdeaPadErrorCode::LstAlreadyWhitelisted
+        );
+        require!(
+            self.lsts.len() < Self::MAX_LSTS,
+            IdeaPadErrorCode::LstRegistryFull
+        );
+
+        self.lsts.push(WhitelistedLst { mint, stake_pool });
+        Ok(())
     }
-}
 
-// Nice in theory probably has to be fleshed out a bit more
-#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
-pub enum RewardType {
-    Additive,  // Include all rewards up until this one that is additive
-    Exclusive, // Only include this reward
+    pub fn remove(&mut self, mint: Pubkey) -> Result<()> {
+        let position = self
+            .lsts
+            .iter()
+            .position(|lst| lst.mint == mint)
+            .ok_or(IdeaPadErrorCode::LstNotWhitelisted)?;
+
+        self.lsts.swap_remove(position);
+        Ok(())
+    }
+
+    pub fn space() -> usize {
+        8 + 4 + (32 + 32) * Self::MAX_LSTS + 1
+    }
 }
 
+// Holds the deposits of one whitelisted lst for a project
 #[account]
-pub struct ContributionReward {
-    pub reward_type: RewardType,
+pub struct ProjectLstVault {
     pub project: Pubkey,
-    // Nft used at the end to gain access to reward
-    pub reward_collection_mint: Pubkey,
-    pub cost: u64,
-    pub quantity: Option<u32>, // if None it is unlimited
+    pub lst_mint: Pubkey,
+    pub source_stake_pool: Pubkey,
+    // Lamport value of the lst backers deposited, owed back to them on claim
+    pub principal_lamports: u64,
+    // Cumulative lst paid out through harvest_lst_yield, including the protocol share
+    pub harvested_yield: u64,
     pub bump: u8,
 }
 
-impl ContributionReward {
+impl ProjectLstVault {
     pub fn init(
         &mut self,
-        reward_type: RewardType,
         project: Pubkey,
-        reward_collection_mint: Pubkey,
-        cost: u64,
-        quantity: Option<u32>,
+        lst_mint: Pubkey,
+        source_stake_pool: Pubkey,
         bump: u8,
     ) -> Result<()> {
-        self.reward_type = reward_type;
         self.project = project;
-        self.reward_collection_mint = reward_collection_mint;
-        self.cost = cost;
-        self.quantity = quantity;
+        self.lst_mint = lst_mint;
+        self.source_stake_pool = source_stake_pool;
+        self.principal_lamports = 0;
+        self.harvested_yield = 0;
         self.bump = bump;
+        Ok(())
+    }
+
+    pub fn add_principal(&mut self, lamports: u64) -> Result<()> {
+        self.principal_lamports = self
+            .principal_lamports
+            .checked_add(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn remove_principal(&mut self, lamports: u64) -> Result<()> {
+        self.principal_lamports = self
+            .principal_lamports
+            .checked_sub(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn record_harvest(&mut self, amount: u64) -> Result<()> {
+        self.harvested_yield = self
+            .harvested_yield
+            .checked_add(amount)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
 
         Ok(())
     }
 
     pub fn space() -> usize {
-        8 + 1 + 32 + 32 + mem::size_of::<u64>() + mem::size_of::<Option<u32>>() + 1
+        8 + 32 + 32 + 32 + mem::size_of::<u64>() + mem::size_of::<u64>() + 1
     }
 }
 
+// A backer's position in a project lst vault
 #[account]
-pub struct StakeVault {
-    pub project: Pubkey,
+pub struct LstDeposit {
+    pub lst_vault: Pubkey,
     pub staker: Pubkey,
-    pub is_claimed: bool,
+    pub principal_lamports: u64,
+    pub lst_deposited: u64,
     pub bump: u8,
 }
 
-impl StakeVault {
-    pub fn init(&mut self, project: Pubkey, staker: Pubkey, bump: i8) -> Result<()> {
-        self.project = project;
+impl LstDeposit {
+    pub fn init(&mut self, lst_vault: Pubkey, staker: Pubkey, bump: u8) -> Result<()> {
+        self.lst_vault = lst_vault;
         self.staker = staker;
+        self.principal_lamports = 0;
+        self.lst_deposited = 0;
+        self.bump = bump;
+        Ok(())
+    }
+
+    // init_if_needed hands back a zeroed account on the first deposit
+    pub fn is_initialized(&self) -> bool {
+        self.staker != Pubkey::default()
+    }
+
+    pub fn record_deposit(&mut self, lamports: u64, lst: u64) -> Result<()> {
+        self.principal_lamports = self
+            .principal_lamports
+            .checked_add(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.lst_deposited = self
+            .lst_deposited
+            .checked_add(lst)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn record_withdrawal(&mut self, lamports: u64, lst: u64) -> Result<()> {
+        self.principal_lamports = self
+            .principal_lamports
+            .checked_sub(lamports)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+        self.lst_deposited = self
+            .lst_deposited
+            .checked_sub(lst)
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?;
+
         Ok(())
     }
 
     pub fn space() -> usize {
-        8 + 32 + 32 + 1
+        8 + 32 + 32 + mem::size_of::<u64>() + mem::size_of::<u64>() + 1
     }
 }
 
-#[account]
-pub struct PoolManager {
-    pub project: Pubkey,
-    pub bump: u8,
+// Exchange rate of an spl stake pool, total_lamports / pool_token_supply
+pub struct PoolRate {
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
 }
 
-impl PoolManager {
-    pub fn init(&mut self, project: Pubkey, staker: Pubkey, bump: i8) -> Result<()> {
-        self.project = project;
-        Ok(())
+impl PoolRate {
+    pub fn load(stake_pool: &AccountInfo, pool_mint: &Pubkey) -> Result<Self> {
+        require_keys_eq!(
+            *stake_pool.owner,
+            spl_stake_pool::id(),
+            IdeaPadErrorCode::InvalidSourceStakePool
+        );
+
+        let pool =
+            spl_stake_pool::state::StakePool::deserialize(&mut &stake_pool.try_borrow_data()?[..])?;
+        require!(
+            pool.is_valid() && pool.pool_mint == *pool_mint,
+            IdeaPadErrorCode::InvalidSourceStakePool
+        );
+        // The rate is only current once the pool has been updated this epoch
+        require!(
+            pool.last_update_epoch >= Clock::get()?.epoch,
+            IdeaPadErrorCode::StaleSourcePoolRate
+        );
+
+        Ok(Self {
+            total_lamports: pool.total_lamports,
+            pool_token_supply: pool.pool_token_supply,
+        })
     }
 
-    pub fn space() -> usize {
-        8 + 32 + 1
+    pub fn to_lamports(&self, lst: u64) -> Result<u64> {
+        Ok((lst as u128)
+            .checked_mul(self.total_lamports as u128)
+            .and_then(|amount| amount.checked_div(self.pool_token_supply as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?)
+    }
+
+    pub fn to_lst(&self, lamports: u64) -> Result<u64> {
+        Ok((lamports as u128)
+            .checked_mul(self.pool_token_supply as u128)
+            .and_then(|amount| amount.checked_div(self.total_lamports as u128))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(IdeaPadErrorCode::NumericalOverflow)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee(numerator: u64, denominator: u64) -> FeeRate {
+        FeeRate {
+            numerator,
+            denominator,
+        }
+    }
+
+    #[test]
+    fn fee_rate_apply_rounds_down() {
+        assert_eq!(fee(1, 3).apply(10).unwrap(), 3);
+        assert_eq!(fee(1, 1000).apply(999).unwrap(), 0);
+        assert_eq!(fee(0, 0).apply(u64::MAX).unwrap(), 0);
+        assert_eq!(fee(5, 0).apply(100).unwrap(), 0);
+    }
+
+    #[test]
+    fn fee_rate_apply_uses_wide_intermediates() {
+        assert_eq!(fee(1, 2).apply(u64::MAX).unwrap(), u64::MAX / 2);
+        assert_eq!(fee(u64::MAX, u64::MAX).apply(u64::MAX).unwrap(), u64::MAX);
+        assert!(fee(2, 1).apply(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn fee_rate_validate_rejects_above_one() {
+        assert!(fee(2, 1).validate().is_err());
+        assert!(fee(1, 1).validate().is_ok());
+        assert!(fee(7, 0).validate().is_err());
+        assert!(fee(0, 0).validate().is_ok());
+    }
+
+    #[test]
+    fn fee_rate_exceeds_compares_ratios() {
+        let max = fee(1, 10);
+        assert!(!fee(10, 100).exceeds(&max));
+        assert!(fee(11, 100).exceeds(&max));
+        assert!(!fee(0, 100).exceeds(&max));
+        assert!(!fee(5, 0).exceeds(&max));
+        assert!(fee(1, 100).exceeds(&fee(0, 0)));
+        assert!(!fee(u64::MAX - 1, u64::MAX).exceeds(&fee(1, 1)));
+    }
+
+    fn test_project(state: ProjectState, redeemption_stamp: Option<i64>) -> Project {
+        Project {
+            state,
+            authority: Pubkey::default(),
+            config: ProjectConfig {
+                redeemption_stamp,
+                min_stake_amount: 0,
+                goal: 1_000,
+                raise_end_stamp: 100,
+                total_raised: 0,
+                early_exit_penalty: FeeRate::default(),
+            },
+            raising_at: None,
+            stake_pool: Pubkey::default(),
+            lst_mint: Pubkey::default(),
+            contribution_reward_count: 1,
+            harvested_yield: 0,
+            last_update_epoch: 0,
+            crank_tips: 0,
+            seed: Vec::new(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn transition_follows_state_machine() {
+        let mut project = test_project(ProjectState::Draft, Some(200));
+        assert!(project.transition(ProjectState::Funded, 0).is_err());
+        project.transition(ProjectState::Raising, 50).unwrap();
+        assert_eq!(project.raising_at, Some(50));
+        assert!(project.transition(ProjectState::Complete, 60).is_err());
+        project.transition(ProjectState::Funded, 150).unwrap();
+        assert!(project.transition(ProjectState::Cancelled, 150).is_err());
+        assert!(project.transition(ProjectState::Complete, 199).is_err());
+        project.transition(ProjectState::Complete, 200).unwrap();
+        assert!(project.transition(ProjectState::Funded, 300).is_err());
+    }
+
+    #[test]
+    fn transition_guards_raising() {
+        let mut project = test_project(ProjectState::Draft, None);
+        assert!(project.transition(ProjectState::Raising, 100).is_err());
+        project.contribution_reward_count = 0;
+        assert!(project.transition(ProjectState::Raising, 0).is_err());
+        project.transition(ProjectState::Cancelled, 0).unwrap();
+        assert!(project.transition(ProjectState::Draft, 0).is_err());
+
+        for terminal in [ProjectState::Failed, ProjectState::Cancelled] {
+            let mut project = test_project(ProjectState::Raising, None);
+            project.transition(terminal, 0).unwrap();
+            assert!(project.transition(ProjectState::Raising, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn is_redeemable_requires_funded_or_complete() {
+        assert!(test_project(ProjectState::Complete, None).is_redeemable(0));
+        assert!(!test_project(ProjectState::Funded, None).is_redeemable(i64::MAX));
+        assert!(!test_project(ProjectState::Funded, Some(200)).is_redeemable(199));
+        assert!(test_project(ProjectState::Funded, Some(200)).is_redeemable(200));
+        for state in [
+            ProjectState::Draft,
+            ProjectState::Raising,
+            ProjectState::Failed,
+            ProjectState::Cancelled,
+        ] {
+            assert!(!test_project(state, Some(200)).is_redeemable(i64::MAX));
+        }
+    }
+
+    fn rewards(tiers: &[(RewardType, u64)]) -> Vec<ContributionReward> {
+        tiers
+            .iter()
+            .enumerate()
+            .map(|(index, (reward_type, cost))| ContributionReward {
+                reward_type: *reward_type,
+                project: Pubkey::default(),
+                index: index as u16,
+                reward_collection_mint: Pubkey::default(),
+                cost: *cost,
+                quantity: None,
+                sold: 0,
+                name: String::new(),
+                symbol: String::new(),
+                uri: String::new(),
+                bump: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entitlements_resolve_additive_and_exclusive_tiers() {
+        let rewards = rewards(&[
+            (RewardType::Additive, 10),
+            (RewardType::Exclusive, 20),
+            (RewardType::Additive, 30),
+            (RewardType::Additive, 40),
+        ]);
+        assert_eq!(ContributionReward::entitlements(&rewards, &[2]), vec![0, 2]);
+        assert_eq!(ContributionReward::entitlements(&rewards, &[1]), vec![1]);
+        assert_eq!(
+            ContributionReward::entitlements(&rewards, &[1, 3]),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(ContributionReward::entitlements(&rewards, &[0, 0]), vec![0]);
+    }
+
+    #[test]
+    fn entitlements_ignore_unknown_tiers() {
+        let rewards = rewards(&[(RewardType::Additive, 10), (RewardType::Additive, 20)]);
+        assert!(ContributionReward::entitlements(&rewards, &[]).is_empty());
+        assert!(ContributionReward::entitlements(&rewards, &[2, u16::MAX]).is_empty());
+        assert!(ContributionReward::entitlements(&[], &[0]).is_empty());
+    }
+
+    #[test]
+    fn pool_rate_converts_and_rounds_down() {
+        let rate = PoolRate {
+            total_lamports: 1_050,
+            pool_token_supply: 1_000,
+        };
+        assert_eq!(rate.to_lamports(1_000).unwrap(), 1_050);
+        assert_eq!(rate.to_lamports(19).unwrap(), 19);
+        assert_eq!(rate.to_lst(1_050).unwrap(), 1_000);
+        assert_eq!(rate.to_lst(21).unwrap(), 20);
+        assert!(rate.to_lst(rate.to_lamports(333).unwrap()).unwrap() <= 333);
+    }
+
+    #[test]
+    fn pool_rate_rejects_empty_pools_and_overflow() {
+        let empty = PoolRate {
+            total_lamports: 0,
+            pool_token_supply: 0,
+        };
+        assert!(empty.to_lamports(1).is_err());
+        assert!(empty.to_lst(1).is_err());
+
+        let rate = PoolRate {
+            total_lamports: 2,
+            pool_token_supply: 1,
+        };
+        assert!(rate.to_lamports(u64::MAX).is_err());
+        assert_eq!(rate.to_lst(u64::MAX).unwrap(), u64::MAX / 2);
+    }
+
+    #[test]
+    fn highest_affordable_picks_costliest_available_tier() {
+        let mut rewards = rewards(&[
+            (RewardType::Additive, 10),
+            (RewardType::Additive, 50),
+            (RewardType::Exclusive, 30),
+        ]);
+        assert_eq!(
+            ContributionReward::highest_affordable(&rewards, 50),
+            Some(1)
+        );
+        assert_eq!(
+            ContributionReward::highest_affordable(&rewards, 49),
+            Some(2)
+        );
+        assert_eq!(
+            ContributionReward::highest_affordable(&rewards, 10),
+            Some(0)
+        );
+        assert_eq!(ContributionReward::highest_affordable(&rewards, 9), None);
+
+        rewards[1].quantity = Some(3);
+        rewards[1].sold = 3;
+        assert_eq!(
+            ContributionReward::highest_affordable(&rewards, u64::MAX),
+            Some(2)
+        );
+        rewards[2].quantity = Some(0);
+        assert_eq!(
+            ContributionReward::highest_affordable(&rewards, u64::MAX),
+            Some(0)
+        );
+        assert_eq!(ContributionReward::highest_affordable(&[], u64::MAX), None);
+    }
+
+    fn test_receipt(lamports: u64, lst_amount: u64) -> Receipt {
+        Receipt {
+            project: Pubkey::default(),
+            stake_vault: Pubkey::default(),
+            contribution_reward: Pubkey::default(),
+            nft_mint: Pubkey::default(),
+            lamports,
+            lst_amount,
+            pending: None,
+            redeemed: false,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn receipt_withdrawal_is_proportional() {
+        let mut receipt = test_receipt(1_000, 900);
+        assert_eq!(receipt.record_withdrawal(300).unwrap(), 333);
+        assert_eq!((receipt.lamports, receipt.lst_amount), (667, 600));
+        assert_eq!(receipt.record_withdrawal(600).unwrap(), 667);
+        assert_eq!((receipt.lamports, receipt.lst_amount), (0, 0));
+    }
+
+    #[test]
+    fn receipt_withdrawal_handles_edges() {
+        let mut receipt = test_receipt(u64::MAX, u64::MAX);
+        assert_eq!(
+            receipt.record_withdrawal(u64::MAX - 1).unwrap(),
+            u64::MAX - 1
+        );
+        assert_eq!(receipt.record_withdrawal(0).unwrap(), 0);
+        assert!(receipt.record_withdrawal(2).is_err());
+
+        let mut receipt = test_receipt(1_000, 0);
+        assert!(receipt.record_withdrawal(1).is_err());
+    }
+
+    fn weighted_allocation(weights: &[u16]) -> (ValidatorAllocation, Vec<Pubkey>) {
+        let vote_accounts: Vec<Pubkey> = weights.iter().map(|_| Pubkey::new_unique()).collect();
+        let mut allocation = ValidatorAllocation {
+            project: Pubkey::default(),
+            validators: Vec::new(),
+            bump: 0,
+        };
+        for (vote_account, weight) in vote_accounts.iter().zip(weights) {
+            allocation.add(*vote_account, *weight).unwrap();
+        }
+        (allocation, vote_accounts)
+    }
+
+    #[test]
+    fn target_lamports_splits_by_weight() {
+        let (allocation, vote_accounts) = weighted_allocation(&[100, 300]);
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[0], 1_000)
+                .unwrap(),
+            250
+        );
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[1], 1_000)
+                .unwrap(),
+            750
+        );
+        assert!(allocation
+            .target_lamports(&Pubkey::new_unique(), 1_000)
+            .is_err());
+    }
+
+    #[test]
+    fn target_lamports_rounds_down_and_handles_edges() {
+        let (allocation, vote_accounts) = weighted_allocation(&[1, 1, 1]);
+        assert_eq!(
+            allocation.target_lamports(&vote_accounts[0], 10).unwrap(),
+            3
+        );
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[2], u64::MAX)
+                .unwrap(),
+            u64::MAX / 3
+        );
+
+        let (allocation, vote_accounts) = weighted_allocation(&[u16::MAX, 0]);
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[0], u64::MAX)
+                .unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[1], u64::MAX)
+                .unwrap(),
+            0
+        );
+
+        let (allocation, vote_accounts) = weighted_allocation(&[0, 0]);
+        assert_eq!(
+            allocation
+                .target_lamports(&vote_accounts[0], 1_000)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn allocation_remove_keeps_other_weights() {
+        let (mut allocation, vote_accounts) = weighted_allocation(&[100, 200, 300]);
+        allocation.remove(vote_accounts[0]).unwrap();
+        assert!(!allocation.contains(&vote_accounts[0]));
+        assert_eq!(
+            allocation.target_lamports(&vote_accounts[2], 500).unwrap(),
+            300
+        );
+
+        allocation.remove(vote_accounts[1]).unwrap();
+        assert!(allocation.remove(vote_accounts[2]).is_err());
+    }
+
+    fn protocol_config(protocol_fee_share: u8) -> ProtocolConfig {
+        ProtocolConfig {
+            admin: Pubkey::default(),
+            pending_admin: None,
+            treasury: Pubkey::default(),
+            epoch_fee: FeeRate::default(),
+            deposit_fee: FeeRate::default(),
+            withdrawal_fee: FeeRate::default(),
+            max_fee: FeeRate::default(),
+            protocol_fee_share,
+            crank_tip: 0,
+            reserve_buffer: 0,
+            paused: false,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn split_harvest_rounds_protocol_share_down() {
+        assert_eq!(
+            protocol_config(10).split_harvest(1_000, 0).unwrap(),
+            (100, 900)
+        );
+        assert_eq!(protocol_config(10).split_harvest(9, 0).unwrap(), (0, 9));
+        assert_eq!(protocol_config(33).split_harvest(100, 0).unwrap(), (33, 67));
+        assert_eq!(
+            protocol_config(0).split_harvest(u64::MAX, 0).unwrap(),
+            (0, u64::MAX)
+        );
+    }
+
+    #[test]
+    fn split_harvest_counts_crank_tips_towards_protocol() {
+        // 1_000 fee lst accrued, 30 of it already tipped to crankers
+        assert_eq!(
+            protocol_config(10).split_harvest(970, 30).unwrap(),
+            (70, 900)
+        );
+        assert_eq!(
+            protocol_config(10).split_harvest(900, 100).unwrap(),
+            (0, 900)
+        );
+        assert_eq!(protocol_config(0).split_harvest(970, 30).unwrap(), (0, 970));
+    }
+
+    #[test]
+    fn crank_tip_is_capped_by_protocol_share() {
+        let rate = PoolRate {
+            total_lamports: 2_000,
+            pool_token_supply: 1_000,
+        };
+        let mut config = protocol_config(10);
+        config.crank_tip = 100;
+        assert_eq!(config.crank_tip_lst(&rate, 10_000, 0).unwrap(), 50);
+        assert_eq!(config.crank_tip_lst(&rate, 300, 0).unwrap(), 30);
+        assert_eq!(config.crank_tip_lst(&rate, 970, 30).unwrap(), 50);
+        assert_eq!(config.crank_tip_lst(&rate, 1_080, 120).unwrap(), 0);
+        assert_eq!(config.crank_tip_lst(&rate, 900, 100).unwrap(), 0);
+
+        let empty = PoolRate {
+            total_lamports: 0,
+            pool_token_supply: 0,
+        };
+        assert_eq!(config.crank_tip_lst(&empty, 10_000, 0).unwrap(), 0);
+        config.crank_tip = 0;
+        assert_eq!(config.crank_tip_lst(&rate, 10_000, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn split_harvest_handles_full_share_and_large_amounts() {
+        assert_eq!(
+            protocol_config(100).split_harvest(u64::MAX, 0).unwrap(),
+            (u64::MAX, 0)
+        );
+        assert_eq!(
+            protocol_config(50).split_harvest(u64::MAX, 0).unwrap(),
+            (u64::MAX / 2, u64::MAX - u64::MAX / 2)
+        );
+        assert_eq!(
+            protocol_config(100)
+                .split_harvest(u64::MAX, u64::MAX)
+                .unwrap(),
+            (u64::MAX, 0)
+        );
     }
 }